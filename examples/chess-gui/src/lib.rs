@@ -27,7 +27,7 @@ lazy_static::lazy_static! {
         map
     };
 
-    static ref GET_CPU_MOVE: Mutex<fn(&Board) -> Move> = Mutex::new(best_move);
+    static ref GET_CPU_MOVE: Mutex<fn(&Board) -> Option<Move>> = Mutex::new(best_move);
     static ref STARTING_BOARD: Mutex<Board> = Mutex::new(Board::default());
 }
 
@@ -38,7 +38,7 @@ const HUMAN_PLAYER: chess_engine::Color = chess_engine::Color::White;
 const AI_PLAYER: chess_engine::Color = chess_engine::Color::Black;
 
 /// Main entry point for launching the GUI.
-pub fn run_gui(get_cpu_move: fn(&Board) -> Move, starting_board: Board) -> iced::Result {
+pub fn run_gui(get_cpu_move: fn(&Board) -> Option<Move>, starting_board: Board) -> iced::Result {
     *GET_CPU_MOVE.lock().unwrap() = get_cpu_move;
     *STARTING_BOARD.lock().unwrap() = starting_board;
 
@@ -53,8 +53,8 @@ pub fn run_gui(get_cpu_move: fn(&Board) -> Move, starting_board: Board) -> iced:
 }
 
 // --- AI Move Functions ---
-pub fn best_move(board: &Board) -> Move { board.get_best_next_move(AI_DEPTH).0 }
-pub fn worst_move(board: &Board) -> Move { board.get_worst_next_move(AI_DEPTH).0 }
+pub fn best_move(board: &Board) -> Option<Move> { board.get_best_next_move(AI_DEPTH).0 }
+pub fn worst_move(board: &Board) -> Option<Move> { board.get_worst_next_move(AI_DEPTH).0 }
 pub fn random_move(board: &Board) -> Move {
     let moves = board.get_legal_moves();
     let mut rng = thread_rng();
@@ -77,7 +77,7 @@ pub enum Message {
     SelectSquare(Position),
     Promote(Piece),
     NewGame,
-    CpuMove(Move),
+    CpuMove(Option<Move>),
 }
 
 // --- Main Application Implementation ---
@@ -117,21 +117,21 @@ impl Application for GameUI {
                     match self.from_square {
                         None => { if self.board.has_ally_piece(pos, HUMAN_PLAYER) { self.from_square = Some(pos); } }
                         Some(from) => {
-                            if is_promotion_move(from, pos, &self.board) {
-                                self.promotion_state = Some((from, pos));
-                                self.from_square = None;
-                            } else {
-                                let m = determine_move(from, pos, &self.board);
-                                self.from_square = None;
-                                return self.play_human_move(m);
+                            self.from_square = None;
+                            if let Some(m) = self.board.move_from_squares(from, pos, None) {
+                                if self.board.is_promotion(m) {
+                                    self.promotion_state = Some((from, pos));
+                                } else {
+                                    return self.play_human_move(m);
+                                }
                             }
                         }
                     }
                 }
             }
-            Message::CpuMove(cpu_move) => {
+            Message::CpuMove(Some(cpu_move)) => {
                 if self.board.get_turn_color() == AI_PLAYER {
-                    if let Some(captured) = self.board.get_piece(cpu_move_target(cpu_move)) { self.add_capture(captured); }
+                    if let Some(captured) = self.board.get_captured_piece(cpu_move) { self.add_capture(captured); }
                     let game_result = self.board.play_move(cpu_move);
                     match game_result {
                         GameResult::Continuing(next_board) => self.board = next_board,
@@ -275,7 +275,7 @@ impl GameUI {
 impl GameUI {
     fn play_human_move(&mut self, m: Move) -> Command<Message> {
         if !self.board.is_legal_move(m, HUMAN_PLAYER) { return Command::none(); }
-        if let Some(captured) = self.board.get_piece(cpu_move_target(m)) { self.add_capture(captured); }
+        if let Some(captured) = self.board.get_captured_piece(m) { self.add_capture(captured); }
         let game_result = self.board.play_move(m);
         self.handle_move_result(game_result)
     }
@@ -297,7 +297,9 @@ impl GameUI {
     fn handle_game_over(&mut self, result: GameResult) {
         self.game_over_message = Some(match result {
             GameResult::Victory(winner) => format!("{} wins!", winner),
-            GameResult::Stalemate => "Stalemate!".to_string(),
+            GameResult::Draw(DrawReason::Stalemate) => "Stalemate!".to_string(),
+            GameResult::Draw(DrawReason::DeadPosition) => "Draw by dead position!".to_string(),
+            GameResult::Draw(DrawReason::VariantRule) => "Draw!".to_string(),
             GameResult::IllegalMove(_) => "Illegal Move!".to_string(),
             GameResult::Continuing(_) => unreachable!(),
         });
@@ -323,31 +325,7 @@ fn get_image_handle(piece: &Piece) -> Option<&'static svg::Handle> {
     PIECE_IMAGES.get(&key)
 }
 
-fn get_move_fn() -> fn(&Board) -> Move { *GET_CPU_MOVE.lock().unwrap() }
-
-fn determine_move(from: Position, to: Position, board: &Board) -> Move {
-    if let Some(Piece::King(..)) = board.get_piece(from) {
-        if (from.get_col() - to.get_col()).abs() == 2 {
-            return if to.get_col() > from.get_col() { Move::KingSideCastle } else { Move::QueenSideCastle };
-        }
-    }
-    Move::Piece(from, to)
-}
-
-fn is_promotion_move(from: Position, to: Position, board: &Board) -> bool {
-    if let Some(Piece::Pawn(color, _)) = board.get_piece(from) {
-        let promotion_rank = if color == chess_engine::Color::White { 7 } else { 0 };
-        return to.get_row() == promotion_rank;
-    }
-    false
-}
-
-fn cpu_move_target(m: Move) -> Position {
-    match m {
-        Move::Piece(_, to) | Move::Promotion(_, to, _) => to,
-        _ => Position::new(-1, -1),
-    }
-}
+fn get_move_fn() -> fn(&Board) -> Option<Move> { *GET_CPU_MOVE.lock().unwrap() }
 
 // --- Styling ---
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
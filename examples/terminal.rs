@@ -15,13 +15,21 @@ fn input(prompt: impl std::fmt::Display) -> String {
     s
 }
 
-fn get_cpu_move(b: &Board, best: bool) -> Move {
+fn get_cpu_move(b: &Board, best: bool) -> Option<Move> {
     let (m, count, _) = if best {
         b.get_best_next_move(4)
     } else {
         b.get_worst_next_move(4)
     };
 
+    let m = match m {
+        Some(m) => m,
+        None => {
+            println!("CPU has no legal moves");
+            return None;
+        }
+    };
+
     print!("CPU evaluated {} moves before choosing to ", count);
     match m {
         Move::Piece(from, to) | Move::Promotion(from, to, _) => {
@@ -45,10 +53,11 @@ fn get_cpu_move(b: &Board, best: bool) -> Move {
         Move::QueenSideCastle => {
             println!("castle queenside")
         }
-        Move::Resign => println!("resign"),
+        Move::Drop(kind, pos) => println!("drop a {} on {}", kind.get_name(), pos),
+        Move::Null => println!("pass"),
     }
 
-    m
+    Some(m)
 }
 
 fn main() -> Result<(), String> {
@@ -63,10 +72,16 @@ fn main() -> Result<(), String> {
 
         let m = if s.is_empty() {
             println!("Waiting for CPU to choose best move...");
-            get_cpu_move(&b, true)
+            match get_cpu_move(&b, true) {
+                Some(m) => m,
+                None => continue,
+            }
         } else if s == "worst" {
             println!("Waiting for CPU to choose worst move...");
-            get_cpu_move(&b, false)
+            match get_cpu_move(&b, false) {
+                Some(m) => m,
+                None => continue,
+            }
         } else if s == "rate" {
             continue;
         } else if s == "pass" {
@@ -82,7 +97,9 @@ fn main() -> Result<(), String> {
             }
             continue;
         } else {
-            match Move::try_from(s) {
+            // moves are read in UCI notation, such as "e2e4" or "e7e8q",
+            // or in any of the formats of `Move::try_from`
+            match parse_uci_move(&b, &s).or_else(|_| Move::try_from(s)) {
                 Ok(m) => m,
                 Err(e) => {
                     eprintln!("{}", e);
@@ -93,9 +110,9 @@ fn main() -> Result<(), String> {
 
         match b.play_move(m) {
             GameResult::Continuing(next_board) => {
+                history.push(format_uci_move(&b, m));
                 b = next_board;
                 println!("{}", b);
-                history.push(m);
             }
 
             GameResult::Victory(winner) => {
@@ -108,8 +125,9 @@ fn main() -> Result<(), String> {
                 eprintln!("{} is an illegal move.", x);
             }
 
-            GameResult::Stalemate => {
-                println!("Drawn game.");
+            GameResult::Draw(reason) => {
+                println!("{}", b);
+                println!("Drawn game by {}.", reason);
                 break;
             }
         }
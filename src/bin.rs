@@ -15,11 +15,28 @@ fn input(prompt: impl std::fmt::Display) -> String {
     s
 }
 
-fn get_cpu_move(b: &Board, best: bool) -> Move {
-    let (m, count, _) = if best {
-        b.get_best_next_move(4)
+fn get_cpu_move(
+    b: &Board,
+    params: &EvalParams,
+    book: Option<&Book>,
+    tablebase: &impl Tablebase,
+    best: bool,
+) -> Option<Move> {
+    let evaluator = TablebaseBoard::new(tablebase, *b, params.with_board(*b));
+    let (m, count, _) = if let (true, Some(book)) = (best, book) {
+        book.get_best_next_move(b, &evaluator, 4)
+    } else if best {
+        evaluator.get_best_next_move(4)
     } else {
-        b.get_worst_next_move(4)
+        evaluator.get_worst_next_move(4)
+    };
+
+    let m = match m {
+        Some(m) => m,
+        None => {
+            println!("CPU has no legal moves");
+            return None;
+        }
     };
 
     print!("CPU evaluated {} moves before choosing to ", count);
@@ -45,14 +62,118 @@ fn get_cpu_move(b: &Board, best: bool) -> Move {
         Move::QueenSideCastle => {
             println!("castle queenside")
         }
-        Move::Resign => println!("resign"),
+        Move::Drop(kind, pos) => println!("drop a {} on {}", kind.get_name(), pos),
+        Move::Null => println!("pass"),
+    }
+
+    Some(m)
+}
+
+// load evaluation parameters from the file given with `--eval <file>`,
+// or fall back to the built-in parameters
+fn load_eval_params() -> Result<EvalParams, String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--eval" {
+            let path = args.next().ok_or("missing file after `--eval`")?;
+            let text = std::fs::read_to_string(&path)
+                .map_err(|e| format!("could not read `{}`: {}", path, e))?;
+            return EvalParams::parse(&text);
+        }
     }
+    Ok(EvalParams::default())
+}
+
+// load the opening book given with `--book <file>`, which the CPU plays
+// from while the game is in it, picking moves at random by their weights
+fn load_book() -> Result<Option<Book>, String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--book" {
+            let path = args.next().ok_or("missing file after `--book`")?;
+            let bytes =
+                std::fs::read(&path).map_err(|e| format!("could not read `{}`: {}", path, e))?;
+            let seed = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |time| time.as_nanos() as u64);
+            return Book::from_bytes(&bytes)
+                .map(|book| {
+                    Some(
+                        book.set_selection(BookSelection::WeightedRandom)
+                            .set_seed(seed),
+                    )
+                })
+                .map_err(|e| format!("invalid book `{}`: {}", path, e));
+        }
+    }
+    Ok(None)
+}
 
-    m
+// load the Syzygy tables in the directories given with `--syzygy-path <dirs>`,
+// which are separated like those of `PATH`
+#[cfg(feature = "syzygy")]
+fn load_tablebase() -> Result<Syzygy, String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--syzygy-path" {
+            let paths = args
+                .next()
+                .ok_or("missing directories after `--syzygy-path`")?;
+            return Syzygy::from_paths(&paths)
+                .map_err(|e| format!("could not read `{}`: {}", paths, e));
+        }
+    }
+    Ok(Syzygy::new())
+}
+
+// without the `syzygy` feature, the CPU plays endgames by search alone
+#[cfg(not(feature = "syzygy"))]
+struct NoTablebase;
+
+#[cfg(not(feature = "syzygy"))]
+impl Tablebase for NoTablebase {
+    fn max_pieces(&self) -> usize {
+        0
+    }
+
+    fn probe_wdl(&self, _: &Board) -> Option<Wdl> {
+        None
+    }
+}
+
+#[cfg(not(feature = "syzygy"))]
+fn load_tablebase() -> Result<NoTablebase, String> {
+    if std::env::args().any(|arg| arg == "--syzygy-path") {
+        return Err(String::from("`--syzygy-path` needs the `syzygy` feature"));
+    }
+    Ok(NoTablebase)
+}
+
+// start from the Chess960 position given with `--chess960 <index>`,
+// the variant given with `--variant <name>`, or the regular starting position
+fn load_start_board() -> Result<Board, String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--chess960" {
+            let index = args.next().ok_or("missing index after `--chess960`")?;
+            return index
+                .parse()
+                .ok()
+                .and_then(Board::chess960)
+                .ok_or(format!("invalid Chess960 index `{}`", index));
+        } else if arg == "--variant" {
+            let name = args.next().ok_or("missing name after `--variant`")?;
+            return Ok(name.parse::<Variant>()?.get_start_position());
+        }
+    }
+    Ok(Board::default())
 }
 
 fn main() -> Result<(), String> {
-    let mut b = Board::default();
+    let params = load_eval_params()?;
+    let book = load_book()?;
+    let tablebase = load_tablebase()?;
+    let mut b = load_start_board()?;
 
     println!("{}", b);
     let mut history = vec![];
@@ -63,10 +184,16 @@ fn main() -> Result<(), String> {
 
         let m = if s.is_empty() {
             println!("Waiting for CPU to choose best move...");
-            get_cpu_move(&b, true)
+            match get_cpu_move(&b, &params, book.as_ref(), &tablebase, true) {
+                Some(m) => m,
+                None => continue,
+            }
         } else if s == "worst" {
             println!("Waiting for CPU to choose worst move...");
-            get_cpu_move(&b, false)
+            match get_cpu_move(&b, &params, book.as_ref(), &tablebase, false) {
+                Some(m) => m,
+                None => continue,
+            }
         } else if s == "rate" {
             continue;
         } else if s == "pass" {
@@ -82,7 +209,9 @@ fn main() -> Result<(), String> {
             }
             continue;
         } else {
-            match Move::try_from(s) {
+            // moves are read in UCI notation, such as "e2e4" or "e7e8q",
+            // or in any of the formats of `Move::try_from`
+            match parse_uci_move(&b, &s).or_else(|_| Move::try_from(s)) {
                 Ok(m) => m,
                 Err(e) => {
                     eprintln!("{}", e);
@@ -93,9 +222,9 @@ fn main() -> Result<(), String> {
 
         match b.play_move(m) {
             GameResult::Continuing(next_board) => {
+                history.push(format_uci_move(&b, m));
                 b = next_board;
                 println!("{}", b);
-                history.push(m);
             }
 
             GameResult::Victory(winner) => {
@@ -108,8 +237,9 @@ fn main() -> Result<(), String> {
                 eprintln!("{} is an illegal move.", x);
             }
 
-            GameResult::Stalemate => {
-                println!("Drawn game.");
+            GameResult::Draw(reason) => {
+                println!("{}", b);
+                println!("Drawn game by {}.", reason);
                 break;
             }
         }
@@ -0,0 +1,62 @@
+//! Lists the moves a Polyglot opening book has for a position.
+//!
+//! ```text
+//! book <book.bin> ["<fen>"]
+//! ```
+//!
+//! The position defaults to the starting position. Moves are written in
+//! UCI notation, from the highest weight to the lowest, along with the
+//! share of the total weight they would be played with.
+extern crate chess_engine;
+use chess_engine::*;
+use std::{fs, process};
+
+const USAGE: &str = "usage: book <book.bin> [<fen>]";
+
+fn run() -> Result<(), String> {
+    let mut args = std::env::args().skip(1);
+    let path = match args.next() {
+        Some(arg) if arg == "-h" || arg == "--help" => return Err(USAGE.to_string()),
+        Some(path) => path,
+        None => return Err(USAGE.to_string()),
+    };
+    let board = match args.next() {
+        Some(fen) => parse_fen(&fen).map_err(|e| format!("invalid FEN: {}", e))?,
+        None => Board::default(),
+    };
+    if let Some(other) = args.next() {
+        return Err(format!("unexpected argument `{}`\n{}", other, USAGE));
+    }
+
+    let bytes = fs::read(&path).map_err(|e| format!("could not read `{}`: {}", path, e))?;
+    let book = Book::from_bytes(&bytes).map_err(|e| format!("invalid book `{}`: {}", path, e))?;
+
+    println!("key {:016x}", polyglot_key(&board));
+    let moves = book.get_moves(&board);
+    if moves.is_empty() {
+        println!("no book moves");
+        return Ok(());
+    }
+    let total: u64 = moves.iter().map(|(_, weight)| *weight as u64).sum();
+    for (m, weight) in moves {
+        let share = if total == 0 {
+            0.0
+        } else {
+            100.0 * weight as f64 / total as f64
+        };
+        println!(
+            "{:<6} weight {:>5} ({:5.1}%)",
+            format_uci_move(&board, m),
+            weight,
+            share
+        );
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,128 @@
+//! Builds a Polyglot opening book from collections of games.
+//!
+//! ```text
+//! build-book games.pgn more-games.pgn -o book.bin --max-ply 16 --min-games 3
+//! ```
+//!
+//! The first `--max-ply` moves of every game are counted, and moves are
+//! weighted by how they scored for the player who made them: two points
+//! for a win and one for a draw. Moves played in fewer than `--min-games`
+//! games, or that never scored, are left out.
+//!
+//! `--results` only counts games with the given results, such as `1-0` to
+//! build a book from White's wins. Games that cannot be parsed are
+//! reported and skipped. The book can be read with the `book` binary, or
+//! given to the `chess` binary with `--book`.
+extern crate chess_engine;
+use chess_engine::*;
+use std::{fs, process};
+
+const USAGE: &str = "usage: build-book <games.pgn>... -o <book.bin> [--max-ply <n>] \
+                     [--min-games <n>] [--results <1-0,0-1,1/2-1/2>]";
+
+struct Options {
+    games: Vec<String>,
+    output: String,
+    max_ply: usize,
+    min_games: u32,
+    results: Option<Vec<String>>,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut args = std::env::args().skip(1);
+    let mut options = Options {
+        games: vec![],
+        output: String::new(),
+        max_ply: 20,
+        min_games: 1,
+        results: None,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("missing value for `{}`", name));
+        match arg.as_str() {
+            "-o" | "--output" => options.output = value(&arg)?,
+            "--max-ply" => {
+                options.max_ply = value(&arg)?
+                    .parse()
+                    .map_err(|_| String::from("invalid maximum ply"))?
+            }
+            "--min-games" => {
+                options.min_games = value(&arg)?
+                    .parse()
+                    .map_err(|_| String::from("invalid minimum game count"))?
+            }
+            "--results" => {
+                let results: Vec<String> = value(&arg)?.split(',').map(String::from).collect();
+                if let Some(result) = results
+                    .iter()
+                    .find(|result| !["1-0", "0-1", "1/2-1/2"].contains(&result.as_str()))
+                {
+                    return Err(format!("invalid result `{}`", result));
+                }
+                options.results = Some(results);
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            other if other.starts_with('-') => {
+                return Err(format!("unexpected argument `{}`\n{}", other, USAGE))
+            }
+            games => options.games.push(games.to_string()),
+        }
+    }
+
+    if options.games.is_empty() || options.output.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    Ok(options)
+}
+
+fn run() -> Result<(), String> {
+    let options = parse_args()?;
+    let mut builder = BookBuilder::default()
+        .set_max_ply(options.max_ply)
+        .set_min_games(options.min_games);
+
+    let (mut counted, mut skipped) = (0, 0);
+    for path in &options.games {
+        let text =
+            fs::read_to_string(path).map_err(|e| format!("could not read `{}`: {}", path, e))?;
+        for (i, game) in parse_pgn(&text).into_iter().enumerate() {
+            let game = match game {
+                Ok(game) => game,
+                Err(e) => {
+                    eprintln!("skipping game {} of `{}`: {}", i + 1, path, e);
+                    skipped += 1;
+                    continue;
+                }
+            };
+            let wanted = match &options.results {
+                Some(results) => results.iter().any(|result| result == game.get_result()),
+                None => true,
+            };
+            if wanted && builder.add_game(&game) {
+                counted += 1;
+            } else {
+                skipped += 1;
+            }
+        }
+    }
+
+    let book = builder.build();
+    fs::write(&options.output, book.to_bytes())
+        .map_err(|e| format!("could not write `{}`: {}", options.output, e))?;
+    println!(
+        "wrote {} entries from {} games to `{}` ({} games skipped)",
+        book.len(),
+        counted,
+        options.output,
+        skipped
+    );
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,242 @@
+//! Runs the engine on an EPD test suite, such as WAC, ECM or STS.
+//!
+//! Reads one EPD record per line, searches each position, and checks the
+//! move found against the record's `bm` (best move) and `am` (avoid move)
+//! operations:
+//!
+//! ```text
+//! 2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id "WAC.001";
+//! ```
+//!
+//! Each position is searched to a fixed depth, or with `--time`, by
+//! iterative deepening until the time runs out. The time is only checked
+//! between depths, so a search may run over it by one iteration. With the
+//! `syzygy` feature, `--syzygy-path` gives directories of Syzygy tables for
+//! the search to look endgames up in.
+extern crate chess_engine;
+use chess_engine::*;
+use std::{
+    fs, process,
+    time::{Duration, Instant},
+};
+
+const USAGE: &str = "usage: epd <suite> [--depth <n>] [--time <seconds>] [--eval <params>] \
+                     [--syzygy-path <dirs>]";
+
+struct Options {
+    suite: String,
+    depth: i32,
+    time: Option<Duration>,
+    eval: Option<String>,
+    syzygy_path: Option<String>,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut args = std::env::args().skip(1);
+    let mut options = Options {
+        suite: String::new(),
+        depth: 4,
+        time: None,
+        eval: None,
+        syzygy_path: None,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("missing value for `{}`", name));
+        match arg.as_str() {
+            "--depth" => {
+                options.depth = value(&arg)?
+                    .parse()
+                    .ok()
+                    .filter(|depth| *depth > 0)
+                    .ok_or_else(|| String::from("invalid depth"))?
+            }
+            "--time" => {
+                let seconds: f64 = value(&arg)?
+                    .parse()
+                    .map_err(|_| String::from("invalid time"))?;
+                options.time = Some(
+                    Duration::try_from_secs_f64(seconds)
+                        .map_err(|_| String::from("invalid time"))?,
+                )
+            }
+            "--eval" => options.eval = Some(value(&arg)?),
+            "--syzygy-path" => options.syzygy_path = Some(value(&arg)?),
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            suite if options.suite.is_empty() => options.suite = suite.to_string(),
+            other => return Err(format!("unexpected argument `{}`\n{}", other, USAGE)),
+        }
+    }
+
+    if options.suite.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    Ok(options)
+}
+
+struct SearchResult {
+    best_move: Option<Move>,
+    depth: i32,
+    nodes: u64,
+    elapsed: Duration,
+}
+
+fn search(
+    params: &EvalParams,
+    tablebase: &impl Tablebase,
+    board: Board,
+    options: &Options,
+) -> SearchResult {
+    let start = Instant::now();
+    let mut result = SearchResult {
+        best_move: None,
+        depth: 0,
+        nodes: 0,
+        elapsed: Duration::ZERO,
+    };
+
+    match options.time {
+        Some(limit) => {
+            // deepen until the time runs out, keeping the deepest result
+            let mut depth = 1;
+            while start.elapsed() < limit {
+                let (best_move, nodes, _) =
+                    TablebaseBoard::new(tablebase, board, params.with_board(board))
+                        .get_best_next_move(depth);
+                result.best_move = best_move;
+                result.depth = depth;
+                result.nodes += nodes;
+                depth += 1;
+            }
+        }
+        None => {
+            let (best_move, nodes, _) =
+                TablebaseBoard::new(tablebase, board, params.with_board(board))
+                    .get_best_next_move(options.depth);
+            result.best_move = best_move;
+            result.depth = options.depth;
+            result.nodes = nodes;
+        }
+    }
+
+    result.elapsed = start.elapsed();
+    result
+}
+
+#[cfg(feature = "syzygy")]
+fn load_tablebase(options: &Options) -> Result<Syzygy, String> {
+    match &options.syzygy_path {
+        Some(paths) => {
+            Syzygy::from_paths(paths).map_err(|e| format!("could not read `{}`: {}", paths, e))
+        }
+        None => Ok(Syzygy::new()),
+    }
+}
+
+// without the `syzygy` feature, endgames are searched like any position
+#[cfg(not(feature = "syzygy"))]
+struct NoTablebase;
+
+#[cfg(not(feature = "syzygy"))]
+impl Tablebase for NoTablebase {
+    fn max_pieces(&self) -> usize {
+        0
+    }
+
+    fn probe_wdl(&self, _: &Board) -> Option<Wdl> {
+        None
+    }
+}
+
+#[cfg(not(feature = "syzygy"))]
+fn load_tablebase(options: &Options) -> Result<NoTablebase, String> {
+    match options.syzygy_path {
+        Some(_) => Err(String::from("`--syzygy-path` needs the `syzygy` feature")),
+        None => Ok(NoTablebase),
+    }
+}
+
+fn format_moves(epd: &Epd, opcode: &str) -> Option<String> {
+    epd.get_operands(opcode)
+        .filter(|moves| !moves.is_empty())
+        .map(|moves| format!("{} {}", opcode, moves.join(" ")))
+}
+
+fn run() -> Result<(), String> {
+    let options = parse_args()?;
+
+    let params = match &options.eval {
+        Some(path) => EvalParams::parse(
+            &fs::read_to_string(path).map_err(|e| format!("could not read `{}`: {}", path, e))?,
+        )?,
+        None => EvalParams::default(),
+    };
+    let tablebase = load_tablebase(&options)?;
+
+    let text = fs::read_to_string(&options.suite)
+        .map_err(|e| format!("could not read `{}`: {}", options.suite, e))?;
+    let mut positions = vec![];
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match parse_epd(line) {
+            Ok(epd) => positions.push((i + 1, epd)),
+            Err(e) => eprintln!("skipping line {}: {}", i + 1, e),
+        }
+    }
+    if positions.is_empty() {
+        return Err(String::from("no positions to search"));
+    }
+
+    let (mut solved, mut failed) = (0, 0);
+    for (line, epd) in &positions {
+        let result = search(&params, &tablebase, *epd.get_board(), &options);
+        let is_solved = result
+            .best_move
+            .is_some_and(|best_move| epd.is_solved_by(best_move));
+        if is_solved {
+            solved += 1;
+        } else {
+            failed += 1;
+        }
+
+        let id = match epd.get_id() {
+            Some(id) => id.to_string(),
+            None => format!("line {}", line),
+        };
+        let found = match result.best_move {
+            Some(m) => format_uci_move(epd.get_board(), m),
+            None => String::from("no move"),
+        };
+        let expected: Vec<String> = ["bm", "am"]
+            .into_iter()
+            .filter_map(|opcode| format_moves(epd, opcode))
+            .collect();
+        println!(
+            "{} {}: found {}, expected {} (depth {}, {} nodes, {:.2}s)",
+            if is_solved { "solved" } else { "failed" },
+            id,
+            found,
+            expected.join(", "),
+            result.depth,
+            result.nodes,
+            result.elapsed.as_secs_f64()
+        );
+    }
+
+    println!(
+        "solved {} of {} positions ({} failed)",
+        solved,
+        solved + failed,
+        failed
+    );
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
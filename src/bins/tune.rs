@@ -0,0 +1,377 @@
+//! Texel-style tuner for the evaluation parameters.
+//!
+//! Reads a file of quiet positions labelled with the result of the game
+//! they were taken from, one per line:
+//!
+//! ```text
+//! rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1 1/2-1/2
+//! 8/5k2/8/8/8/8/1Q6/4K3 w - - c9 "1-0";
+//! ```
+//!
+//! Only the first four FEN fields are used, and the move counters may
+//! follow them before the result. The result may be written as `1-0`,
+//! `0-1`, `1/2-1/2`, or as White's score `1.0`, `0.5` or `0.0`, and may be
+//! wrapped in quotes, brackets or a trailing semicolon.
+//!
+//! The static evaluation is a linear function of the material values and
+//! piece-square tables, so every position is reduced to its list of
+//! parameter coefficients once, and the mean squared error between the
+//! game results and `sigmoid(k * eval)` is minimized by gradient descent.
+//! King material is left untouched since it always cancels out.
+extern crate chess_engine;
+use chess_engine::*;
+use std::{fs, process};
+
+const USAGE: &str = "usage: tune <positions> [-o <output>] [--eval <start params>] \
+                     [--iterations <n>] [--rate <learning rate>]";
+
+const PARAM_COUNT: usize = 6 + 6 * 64;
+
+struct Sample {
+    // sparse (parameter index, coefficient) pairs from White's point of view
+    coefficients: Vec<(usize, f64)>,
+    // White's score: 1.0 for a win, 0.5 for a draw, 0.0 for a loss
+    result: f64,
+}
+
+struct Options {
+    positions: String,
+    output: Option<String>,
+    eval: Option<String>,
+    iterations: usize,
+    rate: f64,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut args = std::env::args().skip(1);
+    let mut options = Options {
+        positions: String::new(),
+        output: None,
+        eval: None,
+        iterations: 1000,
+        rate: 0.1,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("missing value for `{}`", name));
+        match arg.as_str() {
+            "-o" | "--output" => options.output = Some(value(&arg)?),
+            "--eval" => options.eval = Some(value(&arg)?),
+            "--iterations" => {
+                options.iterations = value(&arg)?
+                    .parse()
+                    .map_err(|_| String::from("invalid iteration count"))?
+            }
+            "--rate" => {
+                options.rate = value(&arg)?
+                    .parse()
+                    .map_err(|_| String::from("invalid learning rate"))?
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            positions if options.positions.is_empty() => options.positions = positions.to_string(),
+            other => return Err(format!("unexpected argument `{}`\n{}", other, USAGE)),
+        }
+    }
+
+    if options.positions.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    Ok(options)
+}
+
+fn parse_result(token: &str) -> Option<f64> {
+    match token.trim_matches(|c| matches!(c, '"' | '[' | ']' | '(' | ')' | ';')) {
+        "1-0" | "1.0" => Some(1.0),
+        "0-1" | "0.0" => Some(0.0),
+        "1/2-1/2" | "0.5" | "1/2" => Some(0.5),
+        _ => None,
+    }
+}
+
+fn parse_sample(line: &str) -> Result<Sample, String> {
+    let tokens = line.split_whitespace().collect::<Vec<_>>();
+    if tokens.len() < 5 {
+        return Err(String::from("expected a FEN followed by a result"));
+    }
+
+    let board = parse_fen(&tokens[..4].join(" ")).map_err(|e| e.to_string())?;
+    // skip the move counters, so they are never taken for the result
+    let counters = tokens[4..]
+        .iter()
+        .take(2)
+        .take_while(|token| token.parse::<u32>().is_ok())
+        .count();
+    let result = tokens[4 + counters..]
+        .iter()
+        .find_map(|token| parse_result(token))
+        .ok_or("missing game result")?;
+
+    let mut coefficients = vec![];
+    for row in 0..8 {
+        for col in 0..8 {
+            if let Some(piece) = board.get_piece(Position::new(row, col)) {
+                let (sign, white_row) = match piece.get_color() {
+                    Color::White => (1.0, row),
+                    Color::Black => (-1.0, 7 - row),
+                };
+                let kind = piece.get_kind() as usize;
+                coefficients.push((kind, sign));
+                coefficients.push((6 + kind * 64 + (white_row * 8 + col) as usize, sign));
+            }
+        }
+    }
+
+    Ok(Sample {
+        coefficients,
+        result,
+    })
+}
+
+fn params_to_vec(params: &EvalParams) -> Vec<f64> {
+    let mut result = vec![0.0; PARAM_COUNT];
+    for kind in PieceKind::ALL {
+        result[kind as usize] = params.get_material(kind);
+        for row in 0..8 {
+            for col in 0..8 {
+                result[6 + kind as usize * 64 + (row * 8 + col) as usize] =
+                    params.get_position_weight(kind, Color::White, Position::new(row, col));
+            }
+        }
+    }
+    result
+}
+
+fn vec_to_params(values: &[f64]) -> EvalParams {
+    let mut result = EvalParams::default();
+    for kind in PieceKind::ALL {
+        result.set_material(kind, values[kind as usize]);
+        for row in 0..8 {
+            for col in 0..8 {
+                result.set_position_weight(
+                    kind,
+                    Position::new(row, col),
+                    values[6 + kind as usize * 64 + (row * 8 + col) as usize],
+                );
+            }
+        }
+    }
+    result
+}
+
+fn evaluate(sample: &Sample, values: &[f64]) -> f64 {
+    sample
+        .coefficients
+        .iter()
+        .map(|(i, coefficient)| values[*i] * coefficient)
+        .sum()
+}
+
+fn sigmoid(k: f64, eval: f64) -> f64 {
+    1.0 / (1.0 + (-k * eval).exp())
+}
+
+fn mean_error(samples: &[Sample], values: &[f64], k: f64) -> f64 {
+    samples
+        .iter()
+        .map(|sample| (sample.result - sigmoid(k, evaluate(sample, values))).powi(2))
+        .sum::<f64>()
+        / samples.len() as f64
+}
+
+// find the scaling constant that best maps the current evaluation
+// to game results, so tuning does not just rescale every parameter
+fn find_k(samples: &[Sample], values: &[f64]) -> f64 {
+    let (mut low, mut high) = (0.0001, 1.0);
+    for _ in 0..100 {
+        let a = low + (high - low) / 3.0;
+        let b = high - (high - low) / 3.0;
+        if mean_error(samples, values, a) < mean_error(samples, values, b) {
+            high = b;
+        } else {
+            low = a;
+        }
+    }
+    (low + high) / 2.0
+}
+
+fn gradient(samples: &[Sample], values: &[f64], k: f64) -> Vec<f64> {
+    let mut result = vec![0.0; PARAM_COUNT];
+    for sample in samples {
+        let s = sigmoid(k, evaluate(sample, values));
+        let delta = -2.0 * (sample.result - s) * s * (1.0 - s) * k;
+        for (i, coefficient) in &sample.coefficients {
+            result[*i] += delta * coefficient;
+        }
+    }
+    for g in result.iter_mut() {
+        *g /= samples.len() as f64;
+    }
+    // kings are always on the board, so their material never matters
+    result[PieceKind::King as usize] = 0.0;
+    result
+}
+
+fn tune(samples: &[Sample], values: &mut [f64], options: &Options) {
+    let k = find_k(samples, values);
+    println!(
+        "k = {:.6}, starting error = {:.6}",
+        k,
+        mean_error(samples, values, k)
+    );
+
+    // adam optimizer
+    let (beta1, beta2, epsilon) = (0.9, 0.999, 1e-8);
+    let mut m = vec![0.0; PARAM_COUNT];
+    let mut v = vec![0.0; PARAM_COUNT];
+    for iteration in 1..=options.iterations {
+        let g = gradient(samples, values, k);
+        for i in 0..PARAM_COUNT {
+            m[i] = beta1 * m[i] + (1.0 - beta1) * g[i];
+            v[i] = beta2 * v[i] + (1.0 - beta2) * g[i] * g[i];
+            let m_hat = m[i] / (1.0 - beta1.powi(iteration as i32));
+            let v_hat = v[i] / (1.0 - beta2.powi(iteration as i32));
+            values[i] -= options.rate * m_hat / (v_hat.sqrt() + epsilon);
+        }
+
+        if iteration % 100 == 0 || iteration == options.iterations {
+            println!(
+                "iteration {}: error = {:.6}",
+                iteration,
+                mean_error(samples, values, k)
+            );
+        }
+    }
+}
+
+fn run() -> Result<(), String> {
+    let options = parse_args()?;
+
+    let params = match &options.eval {
+        Some(path) => EvalParams::parse(
+            &fs::read_to_string(path).map_err(|e| format!("could not read `{}`: {}", path, e))?,
+        )?,
+        None => EvalParams::default(),
+    };
+
+    let text = fs::read_to_string(&options.positions)
+        .map_err(|e| format!("could not read `{}`: {}", options.positions, e))?;
+    let mut samples = vec![];
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match parse_sample(line) {
+            Ok(sample) => samples.push(sample),
+            Err(e) => eprintln!("skipping line {}: {}", i + 1, e),
+        }
+    }
+    if samples.is_empty() {
+        return Err(String::from("no positions to tune with"));
+    }
+    println!("loaded {} positions", samples.len());
+
+    let mut values = params_to_vec(&params);
+    tune(&samples, &mut values, &options);
+
+    let tuned = vec_to_params(&values).to_string();
+    match &options.output {
+        Some(path) => {
+            fs::write(path, tuned).map_err(|e| format!("could not write `{}`: {}", path, e))?
+        }
+        None => print!("{}", tuned),
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // parameters with a different value in every slot, so any mix-up
+    // between two slots changes the result
+    fn distinct_params() -> EvalParams {
+        let mut values = vec![0.0; PARAM_COUNT];
+        for (i, value) in values.iter_mut().enumerate() {
+            *value = i as f64 * 0.25 - 20.0;
+        }
+        vec_to_params(&values)
+    }
+
+    #[test]
+    fn test_params_round_trip() {
+        let params = distinct_params();
+        let values = params_to_vec(&params);
+        assert_eq!(vec_to_params(&values), params);
+        assert_eq!(params_to_vec(&vec_to_params(&values)), values);
+    }
+
+    #[test]
+    fn test_coefficients_match_evaluation() {
+        let params = distinct_params();
+        let values = params_to_vec(&params);
+        for fen in [
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+            "r3k2r/pp1n1ppp/2p1b3/q7/3P4/2N2N2/PPQ2PPP/R3KB1R b KQkq - 0 1",
+        ] {
+            let sample = parse_sample(&format!("{} 1-0", fen)).unwrap();
+            let board = parse_fen(fen).unwrap();
+            let expected = params.evaluate(&board, Color::White);
+            assert!(
+                (evaluate(&sample, &values) - expected).abs() < 1e-9,
+                "{}",
+                fen
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_result() {
+        let fen = "8/5k2/8/8/8/8/1Q6/4K3 w - -";
+        for (line, result) in [
+            (format!("{} 1-0", fen), 1.0),
+            (format!("{} 0 1 0-1", fen), 0.0),
+            (format!("{} 12 40 \"1/2-1/2\";", fen), 0.5),
+            (format!("{} c9 \"1-0\";", fen), 1.0),
+            (format!("{} 0.0", fen), 0.0),
+        ] {
+            assert_eq!(parse_sample(&line).unwrap().result, result, "{}", line);
+        }
+        for line in [
+            format!("{} 0 1", fen),
+            format!("{} 0 1 2-0", fen),
+            format!("{} 1", fen),
+        ] {
+            assert!(parse_sample(&line).is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_gradient_step_lowers_error() {
+        let samples = [
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 1/2-1/2",
+            "rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 1-0",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNB1KBNR w KQkq - 0-1",
+            "rnbqkbnr/ppp1pppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0-1",
+        ]
+        .iter()
+        .map(|line| parse_sample(line).unwrap())
+        .collect::<Vec<_>>();
+
+        let mut values = params_to_vec(&EvalParams::default());
+        let k = find_k(&samples, &values);
+        let before = mean_error(&samples, &values, k);
+        let g = gradient(&samples, &values, k);
+        assert_eq!(g[PieceKind::King as usize], 0.0);
+        for (value, g) in values.iter_mut().zip(&g) {
+            *value -= 10.0 * g;
+        }
+        assert!(mean_error(&samples, &values, k) < before);
+    }
+}
@@ -32,7 +32,7 @@ impl BoardBuilder {
         }
 
         for _ in 0..8 {
-            *self.board.get_square(pos) = Square::from(piece.move_to(pos));
+            self.board.set_square(pos, Square::from(piece.move_to(pos)));
             pos = pos.next_right();
         }
 
@@ -46,7 +46,7 @@ impl BoardBuilder {
         }
 
         for _ in 0..8 {
-            *self.board.get_square(pos) = Square::from(piece.move_to(pos));
+            self.board.set_square(pos, Square::from(piece.move_to(pos)));
             pos = pos.next_above();
         }
 
@@ -55,7 +55,7 @@ impl BoardBuilder {
 
     pub fn piece(mut self, piece: Piece) -> Self {
         let pos = piece.get_pos();
-        *self.board.get_square(pos) = Square::from(piece);
+        self.board.set_square(pos, Square::from(piece));
         self
     }
 
@@ -103,6 +103,47 @@ impl BoardBuilder {
         self
     }
 
+    /// Allow a player to castle kingside with the rook on the given file.
+    pub fn set_kingside_castle_rook(mut self, color: Color, col: i32) -> Self {
+        match color {
+            WHITE => self.board.white_castling_rights.kingside = Some(col),
+            BLACK => self.board.black_castling_rights.kingside = Some(col),
+        }
+        self
+    }
+
+    /// Allow a player to castle queenside with the rook on the given file.
+    pub fn set_queenside_castle_rook(mut self, color: Color, col: i32) -> Self {
+        match color {
+            WHITE => self.board.white_castling_rights.queenside = Some(col),
+            BLACK => self.board.black_castling_rights.queenside = Some(col),
+        }
+        self
+    }
+
+    /// Put pieces of a given kind into a player's pocket, for crazyhouse.
+    pub fn pocket(mut self, color: Color, kind: PieceKind, count: u8) -> Self {
+        self.board.pockets[color as usize][kind.index()] = count;
+        self
+    }
+
+    /// Mark the piece on a square as promoted, so that it returns to
+    /// its captor's pocket as a pawn in crazyhouse.
+    pub fn promoted(mut self, pos: Position) -> Self {
+        self.board.promoted |= Board::square_mask(pos);
+        self
+    }
+
+    pub fn set_variant(mut self, variant: Variant) -> Self {
+        self.board = self.board.set_variant(variant);
+        self
+    }
+
+    pub fn set_chess960(mut self, chess960: bool) -> Self {
+        self.board = self.board.set_chess960(chess960);
+        self
+    }
+
     pub fn set_en_passant(mut self, position: Option<Position>) -> Self {
         self.board.en_passant = position;
         self
@@ -113,41 +154,71 @@ impl BoardBuilder {
         self
     }
 
+    pub fn set_halfmove_clock(mut self, halfmove_clock: u16) -> Self {
+        self.board = self.board.set_halfmove_clock(halfmove_clock);
+        self
+    }
+
+    pub fn set_fullmove_number(mut self, fullmove_number: u16) -> Self {
+        self.board = self.board.set_fullmove_number(fullmove_number);
+        self
+    }
+
     pub fn build(self) -> Board {
         self.board
     }
+
+    /// Build the board, checking that the position is legal.
+    pub fn try_build(self) -> Result<Board, PositionError> {
+        self.board.validate()?;
+        Ok(self.board)
+    }
 }
 
+/// The castling rights of one player.
+///
+/// Each right is tied to the file of the rook it castles with, so that
+/// Chess960 positions can keep track of which rook is allowed to castle.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct CastlingRights {
-    kingside: bool,
-    queenside: bool,
+    kingside: Option<i32>,
+    queenside: Option<i32>,
 }
 
 impl Default for CastlingRights {
     fn default() -> Self {
         Self {
-            kingside: true,
-            queenside: true,
+            kingside: Some(7),
+            queenside: Some(0),
         }
     }
 }
 
 impl CastlingRights {
     pub fn can_kingside_castle(&self) -> bool {
-        self.kingside
+        self.kingside.is_some()
     }
 
     pub fn can_queenside_castle(&self) -> bool {
+        self.queenside.is_some()
+    }
+
+    /// The file of the rook that may castle kingside, if any.
+    pub fn get_kingside_rook_col(&self) -> Option<i32> {
+        self.kingside
+    }
+
+    /// The file of the rook that may castle queenside, if any.
+    pub fn get_queenside_rook_col(&self) -> Option<i32> {
         self.queenside
     }
 
     fn disable_kingside(&mut self) {
-        self.kingside = false
+        self.kingside = None
     }
 
     fn disable_queenside(&mut self) {
-        self.queenside = false
+        self.queenside = None
     }
 
     fn disable_all(&mut self) {
@@ -156,11 +227,11 @@ impl CastlingRights {
     }
 
     fn enable_kingside(&mut self) {
-        self.kingside = true
+        self.kingside = Some(7)
     }
 
     fn enable_queenside(&mut self) {
-        self.queenside = true
+        self.queenside = Some(0)
     }
 
     fn enable_all(&mut self) {
@@ -169,6 +240,33 @@ impl CastlingRights {
     }
 }
 
+/// A piece that cannot leave the line between its king and an enemy
+/// piece attacking along that line, without exposing its king.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Pin {
+    pinned: Piece,
+    pinner: Piece,
+    ray: Vec<Position>,
+}
+
+impl Pin {
+    /// The piece that is pinned to its king.
+    pub fn get_pinned(&self) -> Piece {
+        self.pinned
+    }
+
+    /// The enemy piece pinning it.
+    pub fn get_pinner(&self) -> Piece {
+        self.pinner
+    }
+
+    /// The squares between the king and the pinning piece, including the
+    /// pinning piece itself. The pinned piece may only move along these.
+    pub fn get_ray(&self) -> &[Position] {
+        &self.ray
+    }
+}
+
 impl Default for Board {
     fn default() -> Self {
         BoardBuilder::default()
@@ -205,24 +303,29 @@ pub struct Board {
     black_castling_rights: CastlingRights,
 
     turn: Color,
+
+    chess960: bool,
+
+    variant: Variant,
+    // the number of checks given by white and black, for three-check
+    checks: [u8; 2],
+    // the pieces in white's and black's pockets, by kind, for crazyhouse
+    pockets: [[u8; 6]; 2],
+    // the squares holding promoted pieces, indexed like `squares`
+    promoted: u64,
+    // the positions of white's and black's kings, kept up to date by
+    // `set_square`
+    kings: [Option<Position>; 2],
+    // the number of moves since the last capture or pawn move, and the
+    // number of the current move, starting at 1 and counted after black moves
+    halfmove_clock: u16,
+    fullmove_number: u16,
 }
 
 impl Evaluate for Board {
     #[inline]
     fn value_for(&self, ally_color: Color) -> f64 {
-        self.squares
-            .iter()
-            .map(|square| match square.get_piece() {
-                Some(piece) => {
-                    if piece.get_color() == ally_color {
-                        piece.get_weighted_value()
-                    } else {
-                        -piece.get_weighted_value()
-                    }
-                }
-                None => 0.0,
-            })
-            .sum()
+        DEFAULT_EVAL_PARAMS.evaluate(self, ally_color)
     }
 
     #[inline]
@@ -237,17 +340,21 @@ impl Evaluate for Board {
 
     #[inline]
     fn get_legal_moves(&self) -> Vec<Move> {
-        let mut result = vec![];
-        let color = self.get_current_player_color();
-        for square in &self.squares {
-            if let Some(piece) = square.get_piece() {
-                if piece.get_color() == color {
-                    result.extend(piece.get_legal_moves(self))
-                }
-            }
+        if self.variant.get_outcome(self).is_some() {
+            return vec![];
         }
+        self.generate_moves()
+    }
 
-        result
+    #[inline]
+    fn get_no_moves_value(&self, color: Color) -> f64 {
+        // running out of moves wins in antichess, and loses otherwise
+        let current_player_wins = self.variant == Variant::Antichess;
+        if (color == self.turn) == current_player_wins {
+            999999.0
+        } else {
+            -999999.0
+        }
     }
 }
 
@@ -337,7 +444,10 @@ impl Board {
             .piece(Piece::Pawn(WHITE, G5))
             .piece(Piece::Pawn(WHITE, B5))
             .piece(Piece::Pawn(WHITE, C5))
+            .disable_kingside_castle(WHITE)
+            .disable_queenside_castle(WHITE)
             .build()
+            .set_variant(Variant::Horde)
     }
 
     pub fn empty() -> Self {
@@ -349,15 +459,216 @@ impl Board {
             black_castling_rights: CastlingRights::default(),
 
             turn: WHITE,
+
+            chess960: false,
+
+            variant: Variant::Standard,
+            checks: [0; 2],
+            pockets: [[0; 6]; 2],
+            promoted: 0,
+            kings: [None; 2],
+            halfmove_clock: 0,
+            fullmove_number: 1,
+        }
+    }
+
+    /// Create the starting position of a Chess960 game, given its index
+    /// from 0 to 959 in the standard numbering scheme.
+    ///
+    /// Index 518 is the regular starting position. Returns `None` if the
+    /// index is out of range.
+    pub fn chess960(index: u16) -> Option<Self> {
+        if index >= 960 {
+            return None;
+        }
+
+        // the placement of the two knights among the five files left
+        // over after placing the bishops and the queen
+        const KNIGHTS: [(usize, usize); 10] = [
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (0, 4),
+            (1, 2),
+            (1, 3),
+            (1, 4),
+            (2, 3),
+            (2, 4),
+            (3, 4),
+        ];
+
+        let mut n = index as usize;
+        let mut files = [None; 8];
+        files[n % 4 * 2 + 1] = Some(PieceKind::Bishop);
+        n /= 4;
+        files[n % 4 * 2] = Some(PieceKind::Bishop);
+        n /= 4;
+
+        let mut empty = (0..8)
+            .filter(|col| files[*col].is_none())
+            .collect::<Vec<_>>();
+        files[empty.remove(n % 6)] = Some(PieceKind::Queen);
+        n /= 6;
+
+        let (first, second) = KNIGHTS[n];
+        files[empty[first]] = Some(PieceKind::Knight);
+        files[empty[second]] = Some(PieceKind::Knight);
+
+        // the king stands between the two rooks
+        let empty = (0..8)
+            .filter(|col| files[*col].is_none())
+            .collect::<Vec<_>>();
+        files[empty[0]] = Some(PieceKind::Rook);
+        files[empty[1]] = Some(PieceKind::King);
+        files[empty[2]] = Some(PieceKind::Rook);
+
+        let mut builder = BoardBuilder::default()
+            .row(Piece::Pawn(WHITE, A2))
+            .row(Piece::Pawn(BLACK, A7))
+            .set_chess960(true);
+        for (col, kind) in files.iter().enumerate() {
+            if let Some(kind) = kind {
+                builder = builder
+                    .piece(kind.with(WHITE, Position::new(0, col as i32)))
+                    .piece(kind.with(BLACK, Position::new(7, col as i32)));
+            }
+        }
+        for color in [WHITE, BLACK] {
+            builder = builder
+                .set_queenside_castle_rook(color, empty[0] as i32)
+                .set_kingside_castle_rook(color, empty[2] as i32);
+        }
+        Some(builder.build())
+    }
+
+    /// Is this a Chess960 game?
+    ///
+    /// This only changes how castling is written: the rules of Chess960
+    /// castling are a superset of the regular rules, and apply to every board.
+    #[inline]
+    pub fn is_chess960(&self) -> bool {
+        self.chess960
+    }
+
+    /// Get the variant whose rules this board is played by.
+    #[inline]
+    pub fn get_variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Play this board by the rules of a variant, keeping its position.
+    /// Use `Variant::get_start_position` to start a new game instead.
+    #[inline]
+    pub fn set_variant(&self, variant: Variant) -> Self {
+        let mut result = *self;
+        result.variant = variant;
+        result
+    }
+
+    /// Get the number of times a player has given check, which
+    /// is only counted in three-check games.
+    #[inline]
+    pub fn get_checks_given(&self, color: Color) -> u8 {
+        self.checks[color as usize]
+    }
+
+    /// Get the number of pieces of a given kind in a player's pocket,
+    /// which can be dropped onto the board in crazyhouse.
+    #[inline]
+    pub fn get_pocket_count(&self, color: Color, kind: PieceKind) -> u8 {
+        self.pockets[color as usize][kind.index()]
+    }
+
+    /// Is the piece on a square the result of a promotion? This is only
+    /// tracked in crazyhouse.
+    #[inline]
+    pub fn is_promoted(&self, pos: Position) -> bool {
+        pos.is_on_board() && self.promoted & Self::square_mask(pos) != 0
+    }
+
+    #[inline]
+    fn square_mask(pos: Position) -> u64 {
+        1 << ((7 - pos.get_row()) * 8 + pos.get_col())
+    }
+
+    /// Move every piece to the square a function maps it to, optionally
+    /// handing it to the other player.
+    fn map_pieces(&self, map: impl Fn(Position) -> Position, swap_colors: bool) -> Self {
+        let mut result = *self;
+        result.squares = [EMPTY_SQUARE; 64];
+        result.kings = [None; 2];
+        result.promoted = 0;
+        for piece in self.pieces() {
+            let color = match swap_colors {
+                true => !piece.get_color(),
+                false => piece.get_color(),
+            };
+            let pos = map(piece.get_pos());
+            result.add_piece(piece.with_color(color).move_to(pos));
+            if self.is_promoted(piece.get_pos()) {
+                result.promoted |= Self::square_mask(pos);
+            }
         }
+        result.en_passant = self.en_passant.map(map);
+        result
+    }
+
+    /// Get the same position with the colors swapped: every piece is
+    /// handed to the other player and moved to the mirrored rank, so that
+    /// `e2` becomes `e7`. Castling rights, checks given, pockets, and the
+    /// turn swap along with them.
+    ///
+    /// A fair evaluation values a position for one player the same as the
+    /// mirrored position for the other.
+    pub fn mirror(&self) -> Self {
+        let mut result =
+            self.map_pieces(|pos| Position::new(7 - pos.get_row(), pos.get_col()), true);
+        result.white_castling_rights = self.black_castling_rights;
+        result.black_castling_rights = self.white_castling_rights;
+        result.turn = !self.turn;
+        result.checks.swap(0, 1);
+        result.pockets.swap(0, 1);
+        result
+    }
+
+    /// Get the position reflected across the middle of the board, so that
+    /// the a-file becomes the h-file.
+    ///
+    /// The king's side of the board becomes its queen's side, so the
+    /// castling rights are given up.
+    pub fn flip_horizontal(&self) -> Self {
+        let mut result =
+            self.map_pieces(|pos| Position::new(pos.get_row(), 7 - pos.get_col()), false);
+        result.white_castling_rights.disable_all();
+        result.black_castling_rights.disable_all();
+        result
+    }
+
+    /// Get the position turned halfway around with the colors swapped,
+    /// so that each player's pieces stand where the opponent's did, as
+    /// seen from the other side of the board.
+    ///
+    /// Like `flip_horizontal`, this gives up the castling rights.
+    #[inline]
+    pub fn rotate(&self) -> Self {
+        self.mirror().flip_horizontal()
+    }
+
+    /// Mark this board as a Chess960 game, or as a regular game.
+    #[inline]
+    pub fn set_chess960(&self, chess960: bool) -> Self {
+        let mut result = *self;
+        result.chess960 = chess960;
+        result
     }
 
     pub fn rating_bar(&self, len: usize) -> String {
         let (best_m, _, your_best_val) = self.get_best_next_move(2);
         let (_, _, your_lowest_val) = self.get_worst_next_move(2);
         let mut your_val = your_best_val + your_lowest_val;
-        let (_, _, their_best_val) = self.apply_move(best_m).change_turn().get_best_next_move(2);
-        let (_, _, their_lowest_val) = self.apply_move(best_m).change_turn().get_worst_next_move(2);
+        let next = self.apply_move(best_m.unwrap_or(Move::Null)).change_turn();
+        let (_, _, their_best_val) = next.get_best_next_move(2);
+        let (_, _, their_lowest_val) = next.get_worst_next_move(2);
         let mut their_val = their_best_val + their_lowest_val;
 
         if your_val < 0.0 {
@@ -402,15 +713,41 @@ impl Board {
         self.en_passant
     }
 
+    /// Get the number of moves made since the last capture or pawn move,
+    /// which is counted for the fifty move rule.
+    #[inline]
+    pub fn get_halfmove_clock(&self) -> u16 {
+        self.halfmove_clock
+    }
+
+    /// Set the number of moves made since the last capture or pawn move.
+    #[inline]
+    pub fn set_halfmove_clock(&self, halfmove_clock: u16) -> Self {
+        let mut result = *self;
+        result.halfmove_clock = halfmove_clock;
+        result
+    }
+
+    /// Get the number of the current move, which starts at 1 and goes up
+    /// after every move by black.
+    #[inline]
+    pub fn get_fullmove_number(&self) -> u16 {
+        self.fullmove_number
+    }
+
+    /// Set the number of the current move.
+    #[inline]
+    pub fn set_fullmove_number(&self, fullmove_number: u16) -> Self {
+        let mut result = *self;
+        result.fullmove_number = fullmove_number;
+        result
+    }
+
     /// Remove all of the pieces for a given player
     pub fn remove_all(&self, color: Color) -> Self {
         let mut result = *self;
-        for square in &mut result.squares {
-            if let Some(piece) = square.get_piece() {
-                if piece.get_color() == color {
-                    *square = EMPTY_SQUARE
-                }
-            }
+        for piece in self.pieces_of(color) {
+            result.set_square(piece.get_pos(), EMPTY_SQUARE);
         }
 
         result
@@ -419,12 +756,9 @@ impl Board {
     /// Convert all of a given players pieces to queens
     pub fn queen_all(&self, color: Color) -> Self {
         let mut result = *self;
-        for square in &mut result.squares {
-            if let Some(piece) = square.get_piece() {
-                if !piece.is_king() && piece.get_color() == color {
-                    *square = Square::from(Piece::Queen(color, piece.get_pos()))
-                }
-            }
+        for piece in self.pieces_of(color).filter(|piece| !piece.is_king()) {
+            let pos = piece.get_pos();
+            result.set_square(pos, Square::from(Piece::Queen(color, pos)));
         }
 
         result
@@ -456,15 +790,33 @@ impl Board {
             .sum()
     }
 
+    /// Put a square on the board, keeping track of where the kings are.
     #[inline]
-    fn get_square(&mut self, pos: Position) -> &mut Square {
-        &mut self.squares[((7 - pos.get_row()) * 8 + pos.get_col()) as usize]
+    fn set_square(&mut self, pos: Position, square: Square) {
+        let index = ((7 - pos.get_row()) * 8 + pos.get_col()) as usize;
+        let replaced = self.squares[index].get_piece();
+        self.squares[index] = square;
+
+        if let Some(Piece::King(color, _)) = square.get_piece() {
+            self.kings[color as usize] = Some(pos);
+        }
+        if let Some(Piece::King(color, _)) = replaced {
+            if self.kings[color as usize] == Some(pos)
+                && square.get_piece() != Some(Piece::King(color, pos))
+            {
+                // positions set up by hand may have another king left
+                self.kings[color as usize] = self
+                    .pieces_of_kind(color, PieceKind::King)
+                    .last()
+                    .map(|king| king.get_pos());
+            }
+        }
     }
 
     #[inline]
     fn add_piece(&mut self, piece: Piece) {
         let pos = piece.get_pos();
-        *self.get_square(pos) = Square::from(piece);
+        self.set_square(pos, Square::from(piece));
     }
 
     /// Does a square have any piece?
@@ -505,27 +857,41 @@ impl Board {
     /// Otherwise, return false.
     #[inline]
     pub fn has_piece(&self, pos: Position) -> bool {
-        self.get_piece(pos) != None
+        self.get_piece(pos).is_some()
     }
 
     /// If a square at a given position has no piece, return true.
     /// Otherwise, return false.
     #[inline]
     pub fn has_no_piece(&self, pos: Position) -> bool {
-        self.get_piece(pos) == None
+        self.get_piece(pos).is_none()
     }
 
     /// If there is a king on the board, return the position that it sits on.
+    #[inline]
     pub fn get_king_pos(&self, color: Color) -> Option<Position> {
-        let mut king_pos = None;
-        for square in &self.squares {
-            if let Some(Piece::King(c, pos)) = square.get_piece() {
-                if c == color {
-                    king_pos = Some(pos);
-                }
-            }
-        }
-        king_pos
+        self.kings[color as usize]
+    }
+
+    /// Iterate over every piece on the board.
+    pub fn pieces(&self) -> impl Iterator<Item = Piece> + '_ {
+        self.squares.iter().filter_map(|square| square.get_piece())
+    }
+
+    /// Iterate over the pieces of a given color.
+    pub fn pieces_of(&self, color: Color) -> impl Iterator<Item = Piece> + '_ {
+        self.pieces()
+            .filter(move |piece| piece.get_color() == color)
+    }
+
+    /// Iterate over the pieces of a given color and kind.
+    pub fn pieces_of_kind(
+        &self,
+        color: Color,
+        kind: PieceKind,
+    ) -> impl Iterator<Item = Piece> + '_ {
+        self.pieces_of(color)
+            .filter(move |piece| piece.get_kind() == kind)
     }
 
     /// Is a square threatened by an enemy piece?
@@ -555,150 +921,686 @@ impl Board {
         false
     }
 
-    /// Get whether or not the king of a given color is in check.
-    #[inline]
-    pub fn is_in_check(&self, color: Color) -> bool {
-        if let Some(king_pos) = self.get_king_pos(color) {
-            self.is_threatened(king_pos, color)
-        } else {
-            false
+    /// Get the least valuable piece of a given color that attacks a square.
+    // `Option::is_none_or` needs Rust 1.82
+    #[allow(clippy::unnecessary_map_or)]
+    fn get_least_valuable_attacker(&self, pos: Position, color: Color) -> Option<Piece> {
+        let mut result: Option<Piece> = None;
+        for piece in self.pieces_of(color) {
+            if piece.get_pos() != pos
+                && piece.is_legal_attack(pos, self)
+                && result.map_or(true, |best| {
+                    piece.get_material_value() < best.get_material_value()
+                })
+            {
+                result = Some(piece);
+            }
         }
+        result
     }
 
-    fn move_piece(&self, from: Position, to: Position, promotion: Option<Piece>) -> Self {
-        let mut result = *self;
-        result.en_passant = None;
+    /// Get the static exchange evaluation (SEE) of a move: the material
+    /// the moving player wins or loses, in the units of `get_material_value`,
+    /// once both players have made every favorable capture on the target
+    /// square. Either player may stop capturing whenever continuing would
+    /// lose material.
+    ///
+    /// Pieces that attack the target square through another attacker
+    /// (x-rays) join the exchange once the piece in front of them has captured.
+    /// Pins are not considered.
+    ///
+    /// For example, a knight capturing a pawn defended by another pawn
+    /// has a SEE of `1 - 3 = -2`. Non-capturing moves have a SEE of zero, or
+    /// less if the moved piece can be won on its new square. Castling
+    /// always has a SEE of zero.
+    pub fn see(&self, m: Move) -> i32 {
+        let (from, to, promotion) = match m {
+            Move::Piece(from, to) => (from, to, None),
+            Move::Promotion(from, to, promotion) => (from, to, Some(promotion)),
+            _ => return 0,
+        };
+        let piece = match self.get_piece(from) {
+            Some(piece) => piece,
+            None => return 0,
+        };
 
-        if from.is_off_board() || to.is_off_board() {
-            return result;
+        let mut board = *self;
+        let first_capture = match self.get_piece(to) {
+            Some(captured) => captured.get_material_value(),
+            None if piece.is_pawn() && Some(to) == self.en_passant => {
+                board.set_square(to.pawn_back(piece.get_color()), EMPTY_SQUARE);
+                1
+            }
+            None => 0,
+        };
+        board.set_square(from, EMPTY_SQUARE);
+        board.set_square(to, EMPTY_SQUARE);
+
+        let queen_value = Piece::Queen(piece.get_color(), to).get_material_value();
+        let is_last_rank = to.get_row() == 0 || to.get_row() == 7;
+
+        // the material each capture in the sequence wins, assuming the
+        // opponent recaptures
+        let mut gains = vec![first_capture];
+        let mut on_square = match promotion {
+            Some(promotion) => {
+                gains[0] += promotion.get_material_value() - piece.get_material_value();
+                promotion.get_material_value()
+            }
+            None => piece.get_material_value(),
+        };
+
+        let mut color = !piece.get_color();
+        while let Some(attacker) = board.get_least_valuable_attacker(to, color) {
+            let mut gain = on_square - gains[gains.len() - 1];
+            on_square = attacker.get_material_value();
+            if attacker.is_pawn() && is_last_rank {
+                gain += queen_value - on_square;
+                on_square = queen_value;
+            }
+            gains.push(gain);
+
+            board.set_square(attacker.get_pos(), EMPTY_SQUARE);
+            color = !color;
+        }
+
+        // each player only continues the exchange if it is favorable
+        while gains.len() > 1 {
+            let gain = gains.pop().unwrap_or(0);
+            let last = gains.len() - 1;
+            gains[last] = -(-gains[last]).max(gain);
         }
+        gains[0]
+    }
 
-        let from_square = result.get_square(from);
-        if let Some(mut piece) = from_square.get_piece() {
-            *from_square = EMPTY_SQUARE;
+    /// Can the piece on a square be won by the opponent?
+    ///
+    /// A piece is hanging if the opponent has a capture on its square
+    /// with a positive static exchange evaluation.
+    pub fn is_hanging(&self, pos: Position) -> bool {
+        let piece = match self.get_piece(pos) {
+            Some(piece) => piece,
+            None => return false,
+        };
 
-            if piece.is_pawn() && (to.get_row() == 0 || to.get_row() == 7) {
-                piece = match promotion {
-                    // promotion only required to specify piece type
-                    Some(promotion) => {
-                        if promotion.is_king() || promotion.is_pawn() {
-                            // invalid promotion, use default
-                            Piece::Queen(piece.get_color(), piece.get_pos())
-                        } else {
-                            promotion
-                                .with_color(piece.get_color())
-                                .move_to(piece.get_pos())
+        self.pieces_of(!piece.get_color()).any(|attacker| {
+            attacker.is_legal_attack(pos, self)
+                && self.see(Move::Piece(attacker.get_pos(), pos)) > 0
+        })
+    }
+
+    /// Get the pieces of a given color that attack a square, whether the
+    /// square is empty, holds an enemy piece, or holds one of their own.
+    pub fn get_attackers(&self, pos: Position, color: Color) -> Vec<Piece> {
+        // pieces never attack a square their own piece stands on,
+        // so clear it to count defenders too
+        let mut board = *self;
+        if pos.is_on_board() {
+            board.set_square(pos, EMPTY_SQUARE);
+        }
+        self.pieces_of(color)
+            .filter(|piece| piece.get_pos() != pos && piece.is_legal_attack(pos, &board))
+            .collect()
+    }
+
+    /// Get the number of pieces of a given color attacking each square,
+    /// indexed by `SquareIndex`.
+    pub fn get_attack_counts(&self, color: Color) -> [u8; 64] {
+        let mut counts = [0; 64];
+        let mut count = |pos| {
+            if let Some(square) = SquareIndex::from_position(pos) {
+                counts[square.get_index() as usize] += 1;
+            }
+        };
+        let step = |pos: Position, row_step, col_step| {
+            Position::new(pos.get_row() + row_step, pos.get_col() + col_step)
+        };
+        for piece in self.pieces_of(color) {
+            let from = piece.get_pos();
+            match piece {
+                Piece::Pawn(..) => {
+                    let up = from.pawn_up(color);
+                    count(up.next_left());
+                    count(up.next_right());
+                }
+                Piece::Knight(..) | Piece::King(..) => {
+                    for row_step in -2..=2 {
+                        for col_step in -2..=2 {
+                            let to = step(from, row_step, col_step);
+                            if piece.is_knight() && from.is_knight_move(to)
+                                || piece.is_king() && from.is_adjacent_to(to)
+                            {
+                                count(to);
+                            }
+                        }
+                    }
+                }
+                _ => {
+                    for row_step in -1..=1 {
+                        for col_step in -1..=1 {
+                            let slides = match piece {
+                                Piece::Queen(..) => row_step != 0 || col_step != 0,
+                                Piece::Rook(..) => (row_step == 0) != (col_step == 0),
+                                _ => row_step != 0 && col_step != 0,
+                            };
+                            if !slides {
+                                continue;
+                            }
+                            // sliders attack up to and including the first
+                            // piece in their way
+                            let mut to = step(from, row_step, col_step);
+                            while to.is_on_board() {
+                                count(to);
+                                if self.has_piece(to) {
+                                    break;
+                                }
+                                to = step(to, row_step, col_step);
+                            }
                         }
                     }
-                    // queen by default
-                    None => Piece::Queen(piece.get_color(), piece.get_pos()),
                 }
             }
+        }
+        counts
+    }
 
-            if piece.is_starting_pawn() && (from.get_row() - to.get_row()).abs() == 2 {
-                result.en_passant = Some(to.pawn_back(piece.get_color()))
+    /// Get the enemy pieces checking the king of a given color.
+    pub fn get_checkers(&self, color: Color) -> Vec<Piece> {
+        match self.get_king_pos(color) {
+            Some(king_pos) if self.variant.can_be_in_check(self, color) => {
+                self.get_attackers(king_pos, !color)
             }
+            _ => vec![],
+        }
+    }
 
-            result.add_piece(piece.move_to(to));
-
-            let castling_rights = match piece.get_color() {
-                WHITE => &mut result.white_castling_rights,
-                BLACK => &mut result.black_castling_rights,
-            };
-
-            if piece.is_king() {
-                castling_rights.disable_all();
-            } else if piece.is_queenside_rook() {
-                castling_rights.disable_queenside();
-            } else if piece.is_kingside_rook() {
-                castling_rights.disable_kingside();
+    /// Get the first two pieces in a straight line from a square, in the
+    /// direction of the given row and column steps, along with the squares
+    /// up to and including the second piece.
+    fn get_line_pieces(&self, from: Position, step: (i32, i32)) -> Vec<(Piece, Vec<Position>)> {
+        let mut result = vec![];
+        let mut ray = vec![];
+        let mut pos = from;
+        while result.len() < 2 {
+            pos = Position::new(pos.get_row() + step.0, pos.get_col() + step.1);
+            if pos.is_off_board() {
+                break;
+            }
+            ray.push(pos);
+            if let Some(piece) = self.get_piece(pos) {
+                result.push((piece, ray.clone()));
             }
         }
-
         result
     }
 
-    /// Can a given player castle kingside?
-    pub fn can_kingside_castle(&self, color: Color) -> bool {
-        let right_of_king = Position::king_pos(color).next_right();
-        match color {
-            WHITE => {
-                self.has_no_piece(Position::new(0, 5))
-                    && self.has_no_piece(Position::new(0, 6))
-                    && self.get_piece(Position::new(0, 7))
-                        == Some(Piece::Rook(color, Position::new(0, 7)))
-                    && self.white_castling_rights.can_kingside_castle()
-                    && !self.is_in_check(color)
-                    && !self.is_threatened(right_of_king, color)
-                    && !self.is_threatened(right_of_king.next_right(), color)
-            }
-            BLACK => {
-                self.has_no_piece(Position::new(7, 5))
-                    && self.has_no_piece(Position::new(7, 6))
-                    && self.get_piece(Position::new(7, 7))
-                        == Some(Piece::Rook(color, Position::new(7, 7)))
-                    && self.black_castling_rights.can_kingside_castle()
-                    && !self.is_in_check(color)
-                    && !self.is_threatened(right_of_king, color)
-                    && !self.is_threatened(right_of_king.next_right(), color)
+    /// Get the pairs of pieces lined up on the king of a given color: a
+    /// piece of `blocker_color` standing in front of a piece of
+    /// `slider_color` that would attack the king along that line.
+    fn get_x_rays(&self, color: Color, blocker_color: Color, slider_color: Color) -> Vec<Pin> {
+        let king_pos = match self.get_king_pos(color) {
+            Some(king_pos) => king_pos,
+            None => return vec![],
+        };
+        let mut result = vec![];
+        for row_step in -1..=1 {
+            for col_step in -1..=1 {
+                if row_step == 0 && col_step == 0 {
+                    continue;
+                }
+                let line = self.get_line_pieces(king_pos, (row_step, col_step));
+                if let [(blocker, _), (slider, ray)] = line.as_slice() {
+                    let slides = match slider {
+                        Piece::Queen(..) => true,
+                        Piece::Rook(..) => row_step == 0 || col_step == 0,
+                        Piece::Bishop(..) => row_step != 0 && col_step != 0,
+                        _ => false,
+                    };
+                    if slides
+                        && blocker.get_color() == blocker_color
+                        && slider.get_color() == slider_color
+                    {
+                        result.push(Pin {
+                            pinned: *blocker,
+                            pinner: *slider,
+                            ray: ray.clone(),
+                        });
+                    }
+                }
             }
         }
+        result
     }
 
-    /// Can a given player castle queenside?
-    pub fn can_queenside_castle(&self, color: Color) -> bool {
-        match color {
-            WHITE => {
-                self.has_no_piece(Position::new(0, 1))
-                    && self.has_no_piece(Position::new(0, 2))
-                    && self.has_no_piece(Position::new(0, 3))
-                    && self.get_piece(Position::new(0, 0))
-                        == Some(Piece::Rook(color, Position::new(0, 0)))
-                    && self.white_castling_rights.can_queenside_castle()
-                    && !self.is_in_check(color)
-                    && !self.is_threatened(Position::queen_pos(color), color)
-            }
-            BLACK => {
-                self.has_no_piece(Position::new(7, 1))
-                    && self.has_no_piece(Position::new(7, 2))
-                    && self.has_no_piece(Position::new(7, 3))
-                    && self.get_piece(Position::new(7, 0))
-                        == Some(Piece::Rook(color, Position::new(7, 0)))
-                    && self.black_castling_rights.can_queenside_castle()
-                    && !self.is_in_check(color)
-                    && !self.is_threatened(Position::queen_pos(color), color)
-            }
+    /// Get the pieces of a given color that are absolutely pinned to
+    /// their king.
+    pub fn get_pins(&self, color: Color) -> Vec<Pin> {
+        if !self.variant.can_be_in_check(self, color) {
+            return vec![];
         }
+        self.get_x_rays(color, color, !color)
     }
 
-    pub fn get_castling_rights(&self, color: Color) -> CastlingRights {
-        match color {
-            WHITE => self.white_castling_rights,
-            BLACK => self.black_castling_rights,
+    /// Get the pieces of a given color that would give a discovered check
+    /// by moving off the line between one of their own pieces and the
+    /// enemy king.
+    pub fn get_discovered_check_candidates(&self, color: Color) -> Vec<Piece> {
+        if !self.variant.can_be_in_check(self, !color) {
+            return vec![];
         }
+        self.get_x_rays(!color, color, color)
+            .into_iter()
+            .map(|pin| pin.get_pinned())
+            .collect()
     }
 
-pub fn is_legal_move(&self, m: Move, player_color: Color) -> bool {
-            match m {
-            Move::KingSideCastle => self.can_kingside_castle(player_color),
-            Move::QueenSideCastle => self.can_queenside_castle(player_color),
+    /// Get how many steps a square is from `origin`, along the line from
+    /// `origin` toward another square on the same row, column or
+    /// diagonal, if it lies on that line.
+    fn get_steps_along_line(origin: Position, toward: Position, pos: Position) -> Option<i32> {
+        let row_step = (toward.get_row() - origin.get_row()).signum();
+        let col_step = (toward.get_col() - origin.get_col()).signum();
+        let rows = pos.get_row() - origin.get_row();
+        let cols = pos.get_col() - origin.get_col();
+        let steps = match (row_step, col_step) {
+            (0, 0) => return None,
+            (0, _) => cols / col_step,
+            _ => rows / row_step,
+        };
+        if steps > 0 && rows == steps * row_step && cols == steps * col_step {
+            Some(steps)
+        } else {
+            None
+        }
+    }
+
+    /// Would a move leave the king of a given color in check?
+    ///
+    /// Regular moves expose the king if they move a pinned piece off its
+    /// pin, and get out of check only by capturing the single checking
+    /// piece or stepping between it and the king. King moves, en passant
+    /// captures, drops, castling, and moves in atomic chess are played
+    /// out to see.
+    fn leaves_king_in_check(&self, m: Move, color: Color) -> bool {
+        if let Move::Piece(from, to) | Move::Promotion(from, to, _) = m {
+            let is_en_passant = Some(to) == self.en_passant
+                && self.get_piece(from).is_some_and(|piece| piece.is_pawn());
+            if self.variant != Variant::Atomic
+                && !is_en_passant
+                && self.get_king_pos(color) != Some(from)
+            {
+                let unpins = self
+                    .get_pins(color)
+                    .iter()
+                    .any(|pin| pin.get_pinned().get_pos() == from && !pin.get_ray().contains(&to));
+                let evades = match (
+                    self.get_checkers(color).as_slice(),
+                    self.get_king_pos(color),
+                ) {
+                    ([], _) => true,
+                    ([checker], Some(king_pos)) => {
+                        let checker_pos = checker.get_pos();
+                        let steps = |pos| Self::get_steps_along_line(king_pos, checker_pos, pos);
+                        let slides = matches!(
+                            checker,
+                            Piece::Queen(..) | Piece::Rook(..) | Piece::Bishop(..)
+                        );
+                        to == checker_pos
+                            || slides && steps(to).is_some() && steps(to) < steps(checker_pos)
+                    }
+                    // only the king can get out of a double check
+                    _ => false,
+                };
+                return unpins || !evades;
+            }
+        }
+        self.apply_move(m).is_in_check(color)
+    }
+
+    /// Get whether or not the king of a given color is in check.
+    #[inline]
+    pub fn is_in_check(&self, color: Color) -> bool {
+        if let Some(king_pos) = self.get_king_pos(color) {
+            self.variant.can_be_in_check(self, color) && self.is_threatened(king_pos, color)
+        } else {
+            false
+        }
+    }
+
+    /// Does a move capture a piece, including en-passant captures?
+    #[inline]
+    pub fn is_capture(&self, m: Move) -> bool {
+        match m {
+            Move::Piece(from, to) | Move::Promotion(from, to, _) => {
+                self.has_enemy_piece(to, self.turn)
+                    || (Some(to) == self.en_passant
+                        && from.get_col() != to.get_col()
+                        && self.get_piece(from).is_some_and(|piece| piece.is_pawn()))
+            }
+            _ => false,
+        }
+    }
+
+    /// Get the legal moves for the current player, without checking
+    /// whether the game has already been won by a rule of the variant.
+    pub(crate) fn generate_moves(&self) -> Vec<Move> {
+        let mut result = vec![];
+        let color = self.get_current_player_color();
+        for piece in self.pieces_of(color) {
+            result.extend(piece.get_legal_moves(self))
+        }
+
+        if self.variant == Variant::Crazyhouse {
+            // dropping a piece can only block a check, never expose the king
+            let in_check = self.is_in_check(color);
+            for kind in PieceKind::ALL {
+                if self.get_pocket_count(color, kind) == 0 {
+                    continue;
+                }
+                for row in 0..8 {
+                    for col in 0..8 {
+                        let m = Move::Drop(kind, Position::new(row, col));
+                        if self.is_legal_drop(kind, Position::new(row, col), color)
+                            && !(in_check && self.apply_move(m).is_in_check(color))
+                        {
+                            result.push(m);
+                        }
+                    }
+                }
+            }
+        }
+
+        if self.variant.has_forced_captures() && result.iter().any(|m| self.is_capture(*m)) {
+            result.retain(|m| self.is_capture(*m));
+        }
+        result
+    }
+
+    /// Can a piece be dropped on a square, ignoring checks?
+    #[inline]
+    fn is_legal_drop(&self, kind: PieceKind, pos: Position, color: Color) -> bool {
+        self.variant == Variant::Crazyhouse
+            && self.get_pocket_count(color, kind) > 0
+            && pos.is_on_board()
+            && self.has_no_piece(pos)
+            && !(kind == PieceKind::Pawn && (pos.get_row() == 0 || pos.get_row() == 7))
+    }
+
+    /// Remove the pieces caught in an atomic explosion on a square:
+    /// the piece on the square itself, and every piece around it other
+    /// than pawns.
+    fn explode(&mut self, center: Position) {
+        let explosion = core::iter::once(center).chain(Variant::get_explosion(center));
+        for pos in explosion {
+            match self.get_piece(pos) {
+                Some(piece) if pos == center || !piece.is_pawn() => {
+                    self.set_square(pos, EMPTY_SQUARE);
+                    for color in [WHITE, BLACK] {
+                        if piece.is_king() && piece.get_color() == color {
+                            self.get_castling_rights_mut(color).disable_all();
+                        }
+                        if self.get_castling_rook_pos(color, true) == Some(pos) {
+                            self.get_castling_rights_mut(color).disable_kingside();
+                        }
+                        if self.get_castling_rook_pos(color, false) == Some(pos) {
+                            self.get_castling_rights_mut(color).disable_queenside();
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn move_piece(&self, from: Position, to: Position, promotion: Option<Piece>) -> Self {
+        let mut result = *self;
+        result.en_passant = None;
+
+        if from.is_off_board() || to.is_off_board() {
+            return result;
+        }
+
+        if let Some(mut piece) = result.get_piece(from) {
+            result.set_square(from, EMPTY_SQUARE);
+
+            if piece.is_pawn() && (to.get_row() == 0 || to.get_row() == 7) {
+                piece = match promotion {
+                    // promotion only required to specify piece type
+                    Some(promotion) => {
+                        if promotion.is_pawn()
+                            || (promotion.is_king() && self.variant != Variant::Antichess)
+                        {
+                            // invalid promotion, use default
+                            Piece::Queen(piece.get_color(), piece.get_pos())
+                        } else {
+                            promotion
+                                .with_color(piece.get_color())
+                                .move_to(piece.get_pos())
+                        }
+                    }
+                    // queen by default
+                    None => Piece::Queen(piece.get_color(), piece.get_pos()),
+                }
+            }
+
+            if piece.is_pawn() && (from.get_row() - to.get_row()).abs() == 2 {
+                result.en_passant = Some(to.pawn_back(piece.get_color()))
+            }
+
+            result.add_piece(piece.move_to(to));
+
+            if piece.is_king() {
+                result
+                    .get_castling_rights_mut(piece.get_color())
+                    .disable_all();
+            }
+            // moving or capturing a castling rook gives up its right
+            for color in [WHITE, BLACK] {
+                if let Some(rook_pos) = self.get_castling_rook_pos(color, true) {
+                    if rook_pos == from || rook_pos == to {
+                        result.get_castling_rights_mut(color).disable_kingside();
+                    }
+                }
+                if let Some(rook_pos) = self.get_castling_rook_pos(color, false) {
+                    if rook_pos == from || rook_pos == to {
+                        result.get_castling_rights_mut(color).disable_queenside();
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    #[inline]
+    fn get_castling_rights_mut(&mut self, color: Color) -> &mut CastlingRights {
+        match color {
+            WHITE => &mut self.white_castling_rights,
+            BLACK => &mut self.black_castling_rights,
+        }
+    }
+
+    /// Can this pawn move two squares forward, if nothing is in the way?
+    #[inline]
+    pub(crate) fn can_double_push(&self, pawn: &Piece) -> bool {
+        pawn.is_starting_pawn()
+            || (self.variant == Variant::Horde
+                && *pawn == Piece::Pawn(WHITE, pawn.get_pos())
+                && pawn.get_pos().get_row() == 0)
+    }
+
+    /// Get the position of the rook that a player may castle with, if
+    /// they still have the right to castle on that side.
+    pub fn get_castling_rook_pos(&self, color: Color, kingside: bool) -> Option<Position> {
+        let rights = self.get_castling_rights(color);
+        let col = if kingside {
+            rights.get_kingside_rook_col()
+        } else {
+            rights.get_queenside_rook_col()
+        }?;
+        Some(Position::new(Position::king_pos(color).get_row(), col))
+    }
+
+    /// Get the squares the king and rook end up on after castling.
+    ///
+    /// These are the same as in regular chess, no matter where the king
+    /// and rook started.
+    #[inline]
+    fn get_castling_destinations(color: Color, kingside: bool) -> (Position, Position) {
+        let row = Position::king_pos(color).get_row();
+        if kingside {
+            (Position::new(row, 6), Position::new(row, 5))
+        } else {
+            (Position::new(row, 2), Position::new(row, 3))
+        }
+    }
+
+    /// Can a given player castle on the given side? If not, get the
+    /// reason why.
+    ///
+    /// Every square between the king and its destination, and between the
+    /// rook and its destination, must be empty besides the king and rook
+    /// themselves. The king cannot castle out of, through, or into check.
+    fn check_castle(&self, color: Color, kingside: bool) -> Result<(), IllegalMoveReason> {
+        let (king_pos, rook_pos) = match (
+            self.get_king_pos(color),
+            self.get_castling_rook_pos(color, kingside),
+        ) {
+            (Some(king_pos), Some(rook_pos)) => (king_pos, rook_pos),
+            _ => return Err(IllegalMoveReason::NoCastlingRights),
+        };
+        if king_pos.get_row() != rook_pos.get_row()
+            || (rook_pos.get_col() > king_pos.get_col()) != kingside
+            || self.get_piece(rook_pos) != Some(Piece::Rook(color, rook_pos))
+        {
+            return Err(IllegalMoveReason::NoCastlingRights);
+        }
+
+        let (king_to, rook_to) = Self::get_castling_destinations(color, kingside);
+        let row = king_pos.get_row();
+        let span = |a: i32, b: i32| a.min(b)..=a.max(b);
+        for col in span(king_pos.get_col(), king_to.get_col())
+            .chain(span(rook_pos.get_col(), rook_to.get_col()))
+        {
+            let pos = Position::new(row, col);
+            if pos != king_pos && pos != rook_pos && self.has_piece(pos) {
+                return Err(IllegalMoveReason::BlockedPath(pos));
+            }
+        }
+
+        if self.is_in_check(color) {
+            return Err(IllegalMoveReason::CastlingThroughCheck);
+        }
+        for col in span(king_pos.get_col(), king_to.get_col()) {
+            if self.is_threatened(Position::new(row, col), color) {
+                return Err(IllegalMoveReason::CastlingThroughCheck);
+            }
+        }
+
+        // the castling rook may have been shielding the king's destination
+        let castled = self.set_turn(color).apply_move(if kingside {
+            Move::KingSideCastle
+        } else {
+            Move::QueenSideCastle
+        });
+        match castled.is_in_check(color) {
+            true => Err(IllegalMoveReason::CastlingThroughCheck),
+            false => Ok(()),
+        }
+    }
+
+    /// Can a given player castle kingside?
+    #[inline]
+    pub fn can_kingside_castle(&self, color: Color) -> bool {
+        self.check_castle(color, true).is_ok()
+    }
+
+    /// Can a given player castle queenside?
+    #[inline]
+    pub fn can_queenside_castle(&self, color: Color) -> bool {
+        self.check_castle(color, false).is_ok()
+    }
+
+    /// Get the squares a castling move is written with in UCI notation.
+    ///
+    /// Regular games write the king's move, such as `e1g1`, while Chess960
+    /// games write the king capturing its own rook, such as `e1h1`.
+    pub fn get_castling_squares(&self, m: Move) -> Option<(Position, Position)> {
+        let kingside = match m {
+            Move::KingSideCastle => true,
+            Move::QueenSideCastle => false,
+            _ => return None,
+        };
+        let king_pos = self.get_king_pos(self.turn)?;
+        if self.chess960 {
+            Some((king_pos, self.get_castling_rook_pos(self.turn, kingside)?))
+        } else {
+            Some((
+                king_pos,
+                Self::get_castling_destinations(self.turn, kingside).0,
+            ))
+        }
+    }
+
+    /// Get the castling move written with the given squares in UCI
+    /// notation, if any.
+    ///
+    /// The king capturing its own castling rook is always accepted. The
+    /// king moving two squares to its castled square is only accepted in
+    /// regular games, since in Chess960 that can be a regular king move.
+    pub fn get_castling_move(&self, from: Position, to: Position) -> Option<Move> {
+        if self.get_king_pos(self.turn) != Some(from) {
+            return None;
+        }
+        for (kingside, m) in [(true, Move::KingSideCastle), (false, Move::QueenSideCastle)] {
+            if self.get_castling_rook_pos(self.turn, kingside) == Some(to)
+                || (!self.chess960
+                    && (from.get_col() - to.get_col()).abs() == 2
+                    && Self::get_castling_destinations(self.turn, kingside).0 == to)
+            {
+                return Some(m);
+            }
+        }
+        None
+    }
+
+    pub fn get_castling_rights(&self, color: Color) -> CastlingRights {
+        match color {
+            WHITE => self.white_castling_rights,
+            BLACK => self.black_castling_rights,
+        }
+    }
+
+    /// Is a move legal for a given player? When captures are forced, a move
+    /// that does not capture is only legal if no capture is.
+    pub fn is_legal_move(&self, m: Move, player_color: Color) -> bool {
+        self.is_legal_move_unforced(m, player_color) && !self.is_capture_required(m, player_color)
+    }
+
+    /// Is a move legal for a given player, apart from forced captures?
+    /// Pieces generate their moves with this, so that the captures are
+    /// only looked for once.
+    pub(crate) fn is_legal_move_unforced(&self, m: Move, player_color: Color) -> bool {
+        let legal = match m {
+            Move::KingSideCastle => self.can_kingside_castle(player_color),
+            Move::QueenSideCastle => self.can_queenside_castle(player_color),
             Move::Piece(from, to) => match self.get_piece(from) {
                 Some(Piece::Pawn(c, pos)) => {
                     let piece = Piece::Pawn(c, pos);
                     ((if let Some(en_passant) = self.en_passant {
                         (en_passant == from.pawn_up(player_color).next_left()
-                            || en_passant == from.pawn_up(player_color).next_right()
-                                && en_passant == to)
+                            || en_passant == from.pawn_up(player_color).next_right())
+                            && en_passant == to
                             && c == player_color
                     } else {
                         false
                     }) || piece.is_legal_move(to, self) && piece.get_color() == player_color)
-                        && !self.apply_move(m).is_in_check(player_color)
+                        && !self.leaves_king_in_check(m, player_color)
                 }
                 Some(piece) => {
                     piece.is_legal_move(to, self)
                         && piece.get_color() == player_color
-                        && !self.apply_move(m).is_in_check(player_color)
+                        && !self.leaves_king_in_check(m, player_color)
                 }
                 _ => false,
             },
@@ -708,54 +1610,303 @@ pub fn is_legal_move(&self, m: Move, player_color: Color) -> bool {
                         // promotion specific checks
                         piece.is_pawn()
                             && (to.get_row() == 0 || to.get_row() == 7)
-                            && !(promotion.is_king() || promotion.is_pawn())
+                            && !promotion.is_pawn()
+                            && (!promotion.is_king() || self.variant == Variant::Antichess)
                             // regular piece checks
                             && piece.is_legal_move(to, self)
                             && piece.get_color() == player_color
-                            && !self.apply_move(m).is_in_check(player_color)
+                            && !self.leaves_king_in_check(m, player_color)
                     }
                     _ => false,
                 }
             }
-            Move::Resign => true,
+            Move::Drop(kind, pos) => {
+                self.is_legal_drop(kind, pos, player_color)
+                    && !self.leaves_king_in_check(m, player_color)
+            }
+            // passing is only for analysis, it is never a legal move
+            Move::Null => false,
+        };
+        legal && self.variant.is_legal_move(self, m, player_color)
+    }
+
+    /// Must a player capture instead of playing a move that does not?
+    fn is_capture_required(&self, m: Move, color: Color) -> bool {
+        if !self.variant.has_forced_captures() {
+            return false;
+        }
+        let board = if color == self.turn {
+            *self
+        } else {
+            self.change_turn()
+        };
+        !board.is_capture(m)
+            && board.pieces_of(color).any(|piece| {
+                piece
+                    .get_legal_moves(&board)
+                    .into_iter()
+                    .any(|m| board.is_capture(m))
+            })
+    }
+
+    /// Check that a move is legal for the current player, or get the
+    /// rule that it breaks.
+    ///
+    /// A pawn moving to the last rank without a promotion piece is legal,
+    /// and promotes to a queen. This is how the engine writes queen
+    /// promotions itself, so a missing promotion piece is never an error.
+    pub fn check_move(&self, m: Move) -> Result<(), IllegalMoveReason> {
+        let color = self.turn;
+        if self.is_legal_move(m, color) {
+            return Ok(());
+        } else if self.is_legal_move_unforced(m, color) {
+            return Err(IllegalMoveReason::CaptureRequired);
+        }
+
+        Err(match m {
+            Move::Null => IllegalMoveReason::NullMove,
+            Move::KingSideCastle | Move::QueenSideCastle => self
+                .check_castle(color, m == Move::KingSideCastle)
+                .err()
+                .unwrap_or(IllegalMoveReason::VariantRule),
+            Move::Drop(kind, pos) => {
+                if self.variant == Variant::Crazyhouse && self.get_pocket_count(color, kind) == 0 {
+                    IllegalMoveReason::NotInPocket(kind)
+                } else if !self.is_legal_drop(kind, pos, color) {
+                    IllegalMoveReason::InvalidDrop(pos)
+                } else if self.leaves_king_in_check(m, color) {
+                    IllegalMoveReason::LeavesKingInCheck
+                } else {
+                    IllegalMoveReason::VariantRule
+                }
+            }
+            Move::Piece(from, to) | Move::Promotion(from, to, _) => match self.get_piece(from) {
+                None => IllegalMoveReason::NoPieceOnSource(from),
+                Some(piece) if piece.get_color() != color => IllegalMoveReason::NotYourPiece(from),
+                Some(piece) => {
+                    let en_passant = piece.is_pawn()
+                        && Some(to) == self.en_passant
+                        && (to == from.pawn_up(color).next_left()
+                            || to == from.pawn_up(color).next_right());
+                    let promotion = match m {
+                        Move::Promotion(_, _, promotion) => Some(promotion),
+                        _ => None,
+                    };
+                    let invalid_piece = promotion.filter(|promotion| {
+                        promotion.is_pawn()
+                            || (promotion.is_king() && self.variant != Variant::Antichess)
+                    });
+                    if promotion.is_some() && !piece.is_pawn() {
+                        IllegalMoveReason::PromotionNotPawn(from)
+                    } else if promotion.is_some() && to.get_row() != 0 && to.get_row() != 7 {
+                        IllegalMoveReason::PromotionNotOnLastRank(to)
+                    } else if let Some(promotion) = invalid_piece {
+                        IllegalMoveReason::InvalidPromotionPiece(promotion.get_kind())
+                    } else if !(en_passant || piece.is_legal_move(to, self)) {
+                        self.get_movement_failure(piece, to)
+                    } else if self.leaves_king_in_check(m, color) {
+                        // when not in check already, only moving a pinned
+                        // piece can expose the king
+                        if piece.is_king() || self.is_in_check(color) {
+                            IllegalMoveReason::LeavesKingInCheck
+                        } else {
+                            IllegalMoveReason::PiecePinned(from)
+                        }
+                    } else {
+                        IllegalMoveReason::VariantRule
+                    }
+                }
+            },
+        })
+    }
+
+    /// Get the legal moves of the piece on a square, including castling
+    /// for the king.
+    pub fn legal_moves_from(&self, pos: Position) -> Vec<Move> {
+        self.get_legal_moves()
+            .into_iter()
+            .filter(|m| match m {
+                Move::Piece(from, _) | Move::Promotion(from, _, _) => *from == pos,
+                Move::KingSideCastle | Move::QueenSideCastle => {
+                    self.get_king_pos(self.turn) == Some(pos)
+                }
+                _ => false,
+            })
+            .collect()
+    }
+
+    /// Get the legal move that takes a piece from one square to another,
+    /// such as when it is dragged on a board, if there is one.
+    ///
+    /// The king moving onto its castling square, or onto its own castling
+    /// rook, castles. A pawn moving to the last rank promotes to the given
+    /// kind of piece, or a queen if none is given.
+    pub fn move_from_squares(
+        &self,
+        from: Position,
+        to: Position,
+        promotion: Option<PieceKind>,
+    ) -> Option<Move> {
+        let m = match (self.get_castling_move(from, to), promotion) {
+            (Some(castle), _) => castle,
+            (None, Some(kind)) => Move::Promotion(from, to, kind.with(self.turn, to)),
+            (None, None) => Move::Piece(from, to),
+        };
+        self.check_move(m).ok().map(|_| m)
+    }
+
+    /// Get the piece a move captures, including a pawn captured en passant.
+    pub fn get_captured_piece(&self, m: Move) -> Option<Piece> {
+        if !self.is_capture(m) {
+            return None;
+        }
+        match m {
+            Move::Piece(_, to) | Move::Promotion(_, to, _) => match self.get_piece(to) {
+                Some(piece) => Some(piece),
+                // the pawn captured en passant stands behind the square
+                None => self.get_piece(to.pawn_back(self.turn)),
+            },
+            _ => None,
+        }
+    }
+
+    /// Does a move promote a pawn? Pawns moving to the last rank promote
+    /// to a queen when no other piece is given.
+    pub fn is_promotion(&self, m: Move) -> bool {
+        match m {
+            Move::Promotion(..) => true,
+            Move::Piece(from, to) => {
+                self.get_piece(from).is_some_and(|piece| piece.is_pawn())
+                    && (to.get_row() == 0 || to.get_row() == 7)
+            }
+            _ => false,
+        }
+    }
+
+    /// Does a move put the opponent's king in check?
+    ///
+    /// Regular moves give check if the piece attacks the king from the
+    /// square it lands on, or if it uncovers an attack by moving off the
+    /// line between the king and one of its own pieces. En passant
+    /// captures, drops, castling, and moves in atomic chess are played
+    /// out to see.
+    pub fn gives_check(&self, m: Move) -> bool {
+        let color = self.turn;
+        if let Move::Piece(from, to) | Move::Promotion(from, to, _) = m {
+            let piece = self.get_piece(from);
+            let is_en_passant =
+                Some(to) == self.en_passant && piece.is_some_and(|piece| piece.is_pawn());
+            if let (Some(piece), Some(king_pos)) = (piece, self.get_king_pos(!color)) {
+                if self.variant != Variant::Atomic && !is_en_passant {
+                    if !self.variant.can_be_in_check(self, !color) {
+                        return false;
+                    }
+                    let discovered = self.get_discovered_check_candidates(color).contains(&piece)
+                        && Self::get_steps_along_line(king_pos, from, to).is_none();
+                    let landed = match m {
+                        Move::Promotion(_, _, promotion) => promotion.get_kind(),
+                        _ if self.is_promotion(m) => PieceKind::Queen,
+                        _ => piece.get_kind(),
+                    }
+                    .with(color, to);
+                    let mut board = *self;
+                    board.set_square(from, EMPTY_SQUARE);
+                    board.set_square(to, Square::from(landed));
+                    return discovered || landed.is_legal_attack(king_pos, &board);
+                }
+            }
+        }
+        self.apply_move(m).is_in_check(!color)
+    }
+
+    /// Get the reason a piece cannot move to a square, ignoring checks.
+    fn get_movement_failure(&self, piece: Piece, to: Position) -> IllegalMoveReason {
+        let color = piece.get_color();
+        let from = piece.get_pos();
+        // the first piece in the way between two squares on a line
+        let blocker = |mut path: Vec<Position>| {
+            path.pop();
+            path.into_iter().find(|pos| self.has_piece(*pos))
+        };
+
+        if to.is_off_board() || self.has_ally_piece(to, color) {
+            return IllegalMoveReason::InvalidMovement;
+        }
+        let blocked = match piece {
+            Piece::Pawn(..) => {
+                let up = from.pawn_up(color);
+                let captured = Position::new(from.get_row(), to.get_col());
+                if to == up.next_left() || to == up.next_right() {
+                    // capturing a pawn that moved two squares is only
+                    // allowed right after it moved
+                    if self.has_no_piece(to)
+                        && self.get_piece(captured) == Some(Piece::Pawn(!color, captured))
+                        && from.get_row() == if color == WHITE { 4 } else { 3 }
+                    {
+                        return IllegalMoveReason::EnPassantExpired;
+                    }
+                    None
+                } else if to == up || (to == up.pawn_up(color) && self.can_double_push(&piece)) {
+                    [up, to].into_iter().find(|pos| self.has_piece(*pos))
+                } else {
+                    None
+                }
+            }
+            Piece::Queen(..) | Piece::Rook(..) if from.is_orthogonal_to(to) => {
+                blocker(from.orthogonals_to(to))
+            }
+            Piece::Queen(..) | Piece::Bishop(..) if from.is_diagonal_to(to) => {
+                blocker(from.diagonals_to(to))
+            }
+            _ => None,
+        };
+        match blocked {
+            Some(pos) => IllegalMoveReason::BlockedPath(pos),
+            None => IllegalMoveReason::InvalidMovement,
         }
     }
 
-    /// Does the respective player have sufficient material?
+    /// Could the respective player checkmate the opponent by any sequence
+    /// of legal moves, assuming the opponent cooperates?
+    ///
+    /// A player cannot checkmate with
+    /// 1. Only a king
+    /// 2. A king and a knight, against a lone king
+    /// 3. A king and bishops that all stand on squares of the same color,
+    ///    against a king and any bishops on squares of that same color
+    ///
+    /// Note that a king and two knights can checkmate, even though they
+    /// cannot force it. See `has_winning_material` for that.
     pub fn has_sufficient_material(&self, color: Color) -> bool {
-        let mut pieces = vec![];
+        // [light squared bishops, dark squared bishops, knights, others]
+        let mut ours = [0; 4];
+        let mut theirs = [0; 4];
         for square in &self.squares {
             if let Some(piece) = square.get_piece() {
-                if piece.get_color() == color {
-                    pieces.push(piece);
+                let pos = piece.get_pos();
+                let counts = if piece.get_color() == color {
+                    &mut ours
+                } else {
+                    &mut theirs
+                };
+                match piece {
+                    Piece::King(..) => {}
+                    Piece::Bishop(..) if (pos.get_row() + pos.get_col()) % 2 == 1 => counts[0] += 1,
+                    Piece::Bishop(..) => counts[1] += 1,
+                    Piece::Knight(..) => counts[2] += 1,
+                    _ => counts[3] += 1,
                 }
             }
         }
 
-        pieces.sort();
-
-        if pieces.len() == 0 {
-            false
-        } else if pieces.len() == 1 && pieces[0].is_king() {
-            false
-        } else if pieces.len() == 2 && pieces[0].is_king() && pieces[1].is_knight() {
-            false
-        } else if pieces.len() == 2 && pieces[0].is_king() && pieces[1].is_bishop() {
-            false
-        } else if pieces.len() == 3
-            && pieces[0].is_king()
-            && pieces[1].is_knight()
-            && pieces[2].is_knight()
-        {
-            false
-        } else if pieces.len() == 3
-            && pieces[0].is_king()
-            && pieces[1].is_bishop()
-            && pieces[2].is_bishop()
-        {
-            false
-        } else {
-            true
+        let their_total: i32 = theirs.iter().sum();
+        match ours {
+            [_, _, _, others] if others > 0 => true,
+            [0, 0, 0, _] => false,
+            [0, 0, 1, _] => their_total > 0,
+            [_, 0, 0, _] => their_total > theirs[0],
+            [0, _, 0, _] => their_total > theirs[1],
+            _ => true,
         }
     }
 
@@ -765,11 +1916,41 @@ pub fn is_legal_move(&self, m: Move, player_color: Color) -> bool {
         !self.has_sufficient_material(color)
     }
 
+    /// Does the respective player have enough material to force checkmate?
+    ///
+    /// Without pawns, a king with a single minor piece or with two knights
+    /// cannot force checkmate. This is a hint for the evaluation, and not a
+    /// rule that ends the game.
+    pub fn has_winning_material(&self, color: Color) -> bool {
+        let mut minors = 0;
+        let mut knights = 0;
+        for piece in self.pieces_of(color) {
+            match piece {
+                Piece::King(..) => {}
+                Piece::Knight(..) => {
+                    minors += 1;
+                    knights += 1;
+                }
+                Piece::Bishop(..) => minors += 1,
+                _ => return true,
+            }
+        }
+        minors > 1 && !(minors == 2 && knights == 2)
+    }
+
+    /// Is the position dead, meaning neither player can checkmate the
+    /// other by any sequence of legal moves?
+    ///
+    /// This covers king against king, king and minor piece against king,
+    /// and kings with bishops that all stand on squares of the same color.
+    #[inline]
+    pub fn is_dead_position(&self) -> bool {
+        self.has_insufficient_material(WHITE) && self.has_insufficient_material(BLACK)
+    }
+
     /// Is the current player in stalemate?
     pub fn is_stalemate(&self) -> bool {
-        (self.get_legal_moves().is_empty() && !self.is_in_check(self.get_current_player_color()))
-            || (self.has_insufficient_material(self.turn)
-                && self.has_insufficient_material(!self.turn))
+        self.get_legal_moves().is_empty() && !self.is_in_check(self.get_current_player_color())
     }
 
     /// Is the current player in checkmate?
@@ -777,6 +1958,105 @@ pub fn is_legal_move(&self, m: Move, player_color: Color) -> bool {
         self.is_in_check(self.get_current_player_color()) && self.get_legal_moves().is_empty()
     }
 
+    /// Check that the position could arise in a legal game of the
+    /// board's variant.
+    ///
+    /// This rejects missing or extra kings, impossible piece counts,
+    /// pawns on the first or last rank, the side not to move being in check,
+    /// castling rights without a king and rook in place, and en passant
+    /// squares that no pawn could have just skipped.
+    pub fn validate(&self) -> Result<(), PositionError> {
+        for color in [WHITE, BLACK] {
+            let horde = self.variant == Variant::Horde && color == WHITE;
+            let (mut kings, mut pawns, mut pieces) = (0, 0, 0);
+            for piece in self.pieces_of(color) {
+                pieces += 1;
+                if piece.is_king() {
+                    kings += 1;
+                } else if piece.is_pawn() {
+                    pawns += 1;
+                    let row = piece.get_pos().get_row();
+                    if row == 7 - Position::king_pos(color).get_row()
+                        || (row == Position::king_pos(color).get_row() && !horde)
+                    {
+                        return Err(PositionError::PawnOnBackRank(piece.get_pos()));
+                    }
+                }
+            }
+
+            if self.variant != Variant::Antichess {
+                let expected = if horde { 0 } else { 1 };
+                if kings < expected {
+                    return Err(PositionError::MissingKing(color));
+                } else if kings > expected {
+                    return Err(PositionError::TooManyKings(color));
+                }
+            }
+            // pieces return to play from the pockets in crazyhouse,
+            // and the horde starts with more than sixteen
+            if !horde && self.variant != Variant::Crazyhouse {
+                if pawns > 8 {
+                    return Err(PositionError::TooManyPawns(color));
+                } else if pieces > 16 {
+                    return Err(PositionError::TooManyPieces(color));
+                }
+            }
+
+            for kingside in [true, false] {
+                if let Some(rook_pos) = self.get_castling_rook_pos(color, kingside) {
+                    let king_pos = self
+                        .get_king_pos(color)
+                        .filter(|pos| pos.get_row() == rook_pos.get_row())
+                        .filter(|pos| self.chess960 || *pos == Position::king_pos(color));
+                    let in_place = match (king_pos, self.get_piece(rook_pos)) {
+                        (Some(king_pos), Some(Piece::Rook(c, _))) => {
+                            c == color && (rook_pos.get_col() > king_pos.get_col()) == kingside
+                        }
+                        _ => false,
+                    };
+                    if !in_place {
+                        return Err(PositionError::InvalidCastlingRights(color));
+                    }
+                }
+            }
+        }
+
+        if self.is_in_check(!self.turn) {
+            return Err(PositionError::OpponentInCheck);
+        }
+        if let Some(king_pos) = self.get_king_pos(self.turn) {
+            if self.is_in_check(self.turn) {
+                let checkers = self
+                    .squares
+                    .iter()
+                    .filter_map(|square| square.get_piece())
+                    .filter(|piece| {
+                        piece.get_color() != self.turn && piece.is_legal_attack(king_pos, self)
+                    })
+                    .count();
+                if checkers > 2 || self.variant == Variant::RacingKings {
+                    return Err(PositionError::ImpossibleCheck);
+                }
+            }
+        }
+
+        if let Some(ep) = self.en_passant {
+            // the square is right behind an enemy pawn that just moved two
+            // squares, so both it and the pawn's starting square are empty
+            let pawn_pos = ep.pawn_back(self.turn);
+            let row = if self.turn == WHITE { 5 } else { 2 };
+            let valid = ep.get_row() == row
+                && self.has_no_piece(ep)
+                && self.has_no_piece(ep.pawn_up(self.turn))
+                && self.get_piece(pawn_pos) == Some(Piece::Pawn(!self.turn, pawn_pos));
+            if !valid {
+                return Err(PositionError::InvalidEnPassant(ep));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Change the current turn to the next player.
     #[inline]
     pub fn change_turn(mut self) -> Self {
@@ -784,30 +2064,80 @@ pub fn is_legal_move(&self, m: Move, player_color: Color) -> bool {
         self
     }
 
-    fn apply_move(&self, m: Move) -> Self {
-        match m {
-            Move::KingSideCastle => {
-                if let Some(king_pos) = self.get_king_pos(self.turn) {
-                    let rook_pos = match self.turn {
-                        WHITE => Position::new(0, 7),
-                        BLACK => Position::new(7, 7),
-                    };
-                    self.move_piece(king_pos, rook_pos.next_left(), None)
-                        .move_piece(rook_pos, king_pos.next_right(), None)
-                } else {
-                    *self
+    pub(crate) fn apply_move(&self, m: Move) -> Self {
+        let mut result = self.apply_move_unchecked(m);
+        let is_pawn_move = match m {
+            Move::Piece(from, _) | Move::Promotion(from, _, _) => {
+                self.get_piece(from).is_some_and(|piece| piece.is_pawn())
+            }
+            Move::Drop(kind, _) => kind == PieceKind::Pawn,
+            _ => false,
+        };
+        result.halfmove_clock = if is_pawn_move || self.is_capture(m) {
+            0
+        } else {
+            self.halfmove_clock.saturating_add(1)
+        };
+        if self.turn == BLACK {
+            result.fullmove_number = self.fullmove_number.saturating_add(1);
+        }
+        match self.variant {
+            Variant::ThreeCheck if result.is_in_check(!self.turn) => {
+                result.checks[self.turn as usize] += 1;
+            }
+            Variant::Atomic if self.is_capture(m) => {
+                if let Move::Piece(_, to) | Move::Promotion(_, to, _) = m {
+                    result.explode(to);
                 }
             }
-            Move::QueenSideCastle => {
-                if let Some(king_pos) = self.get_king_pos(self.turn) {
-                    let rook_pos = match self.turn {
-                        WHITE => Position::new(0, 0),
-                        BLACK => Position::new(7, 0),
+            Variant::Crazyhouse => {
+                if let Move::Piece(from, to) | Move::Promotion(from, to, _) = m {
+                    // captured pieces go to the captor's pocket, and promoted
+                    // pieces go back to being pawns
+                    let captured = match self.get_piece(to) {
+                        Some(_) if self.is_promoted(to) => Some(PieceKind::Pawn),
+                        Some(piece) => Some(piece.get_kind()),
+                        None if self.is_capture(m) => Some(PieceKind::Pawn),
+                        None => None,
                     };
-                    self.move_piece(king_pos, king_pos.next_left().next_left(), None)
-                        .move_piece(rook_pos, king_pos.next_left(), None)
-                } else {
-                    *self
+                    if let Some(kind) = captured {
+                        result.pockets[self.turn as usize][kind.index()] += 1;
+                    }
+
+                    let promotes = self.get_piece(from).is_some_and(|piece| piece.is_pawn())
+                        && (to.get_row() == 0 || to.get_row() == 7);
+                    result.promoted &= !(Self::square_mask(from) | Self::square_mask(to));
+                    if promotes || self.is_promoted(from) {
+                        result.promoted |= Self::square_mask(to);
+                    }
+                }
+            }
+            _ => {}
+        }
+        result
+    }
+
+    fn apply_move_unchecked(&self, m: Move) -> Self {
+        match m {
+            Move::KingSideCastle | Move::QueenSideCastle => {
+                let kingside = m == Move::KingSideCastle;
+                match (
+                    self.get_king_pos(self.turn),
+                    self.get_castling_rook_pos(self.turn, kingside),
+                ) {
+                    (Some(king_pos), Some(rook_pos)) => {
+                        let (king_to, rook_to) =
+                            Self::get_castling_destinations(self.turn, kingside);
+                        let mut result = *self;
+                        result.en_passant = None;
+                        result.set_square(king_pos, EMPTY_SQUARE);
+                        result.set_square(rook_pos, EMPTY_SQUARE);
+                        result.add_piece(Piece::King(self.turn, king_to));
+                        result.add_piece(Piece::Rook(self.turn, rook_to));
+                        result.get_castling_rights_mut(self.turn).disable_all();
+                        result
+                    }
+                    _ => *self,
                 }
             }
 
@@ -821,31 +2151,41 @@ pub fn is_legal_move(&self, m: Move, player_color: Color) -> bool {
                         || en_passant == from.pawn_up(player_color).next_right())
                         && en_passant == to
                     {
-                        result.squares[((7 - en_passant.pawn_back(player_color).get_row()) * 8
-                            + en_passant.get_col())
-                            as usize] = EMPTY_SQUARE;
+                        result.set_square(en_passant.pawn_back(player_color), EMPTY_SQUARE);
                     }
                 }
 
                 result
             }
             Move::Promotion(from, to, promotion) => self.move_piece(from, to, Some(promotion)),
-            Move::Resign => self.remove_all(self.turn).queen_all(!self.turn),
+            Move::Drop(kind, pos) => {
+                let mut result = *self;
+                result.en_passant = None;
+                result.pockets[self.turn as usize][kind.index()] -= 1;
+                result.add_piece(kind.with(self.turn, pos));
+                result
+            }
+            Move::Null => {
+                let mut result = *self;
+                result.en_passant = None;
+                result
+            }
         }
     }
 
     /// Play a move and confirm it is legal.
     pub fn play_move(&self, m: Move) -> GameResult {
         let current_color = self.get_turn_color();
-
-        if m == Move::Resign {
-            GameResult::Victory(!current_color)
-        } else if self.is_legal_move(m, current_color) {
+        if self.is_legal_move(m, current_color) {
             let next_turn = self.apply_move(m).change_turn();
-            if next_turn.is_checkmate() {
+            if let Some(result) = self.variant.get_outcome(&next_turn) {
+                result
+            } else if next_turn.is_checkmate() {
                 GameResult::Victory(current_color)
             } else if next_turn.is_stalemate() {
-                GameResult::Stalemate
+                GameResult::Draw(DrawReason::Stalemate)
+            } else if self.variant.has_dead_positions() && next_turn.is_dead_position() {
+                GameResult::Draw(DrawReason::DeadPosition)
             } else {
                 GameResult::Continuing(next_turn)
             }
@@ -854,3 +2194,570 @@ pub fn is_legal_move(&self, m: Move, player_color: Color) -> bool {
         }
     }
 }
+
+/// Count the positions at the end of every sequence of legal moves of a
+/// given length, to test move generation against known counts.
+#[cfg(test)]
+pub(crate) fn perft(board: &Board, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    board
+        .get_legal_moves()
+        .into_iter()
+        .map(|m| perft(&board.apply_eval_move(m), depth - 1))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{parse_fen, parse_fen_lenient};
+
+    #[test]
+    fn test_see() {
+        // undefended pawn
+        let board = parse_fen("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1").unwrap();
+        assert_eq!(board.see(Move::Piece(E1, E5)), 1);
+
+        // pawn defended by a knight, with a queen x-raying through the bishop
+        let board = parse_fen("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1").unwrap();
+        assert_eq!(board.see(Move::Piece(D3, E5)), -2);
+
+        // doubled rooks against a rook backed up by a queen
+        let board = parse_fen("3qk3/8/3r4/3p4/8/8/3R4/3RK3 w - - 0 1").unwrap();
+        assert_eq!(board.see(Move::Piece(D2, D5)), -4);
+        let board = parse_fen("4k3/8/3r4/3p4/8/8/3R4/3RK3 w - - 0 1").unwrap();
+        assert_eq!(board.see(Move::Piece(D2, D5)), 1);
+
+        // moving a queen onto a square defended by a pawn
+        let board = parse_fen("4k3/8/4p3/8/8/8/8/3QK3 w - - 0 1").unwrap();
+        assert_eq!(board.see(Move::Piece(D1, D5)), -9);
+        assert_eq!(board.see(Move::Piece(D1, D4)), 0);
+
+        // en passant
+        let board = parse_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        assert_eq!(board.see(Move::Piece(E5, D6)), 1);
+    }
+
+    #[test]
+    fn test_is_hanging() {
+        let board = parse_fen("4k3/8/8/4p3/3P4/1N6/8/4K3 w - - 0 1").unwrap();
+        assert!(board.is_hanging(E5));
+        assert!(!board.is_hanging(D4));
+        assert!(!board.is_hanging(B3));
+    }
+
+    #[test]
+    fn test_dead_position() {
+        for fen in [
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/4KN2 w - - 0 1",
+            "4k3/8/8/8/8/8/8/2b1K3 w - - 0 1",
+            "2b1k3/8/8/8/8/8/8/4KB2 w - - 0 1",
+            "4k3/8/8/8/8/8/8/B1B1K3 w - - 0 1",
+        ] {
+            assert!(parse_fen(fen).unwrap().is_dead_position(), "{}", fen);
+        }
+
+        for fen in [
+            // two knights cannot force mate, but mate is still possible
+            "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1",
+            // bishops on opposite colors
+            "3bk3/8/8/8/8/8/8/4KB2 w - - 0 1",
+            "4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1",
+            // a knight can mate against a bishop that blocks its own king
+            "4kb2/8/8/8/8/8/8/4KN2 w - - 0 1",
+            "4k3/4p3/8/8/8/8/8/4K3 w - - 0 1",
+        ] {
+            assert!(!parse_fen(fen).unwrap().is_dead_position(), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_winning_material() {
+        let board = parse_fen("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1").unwrap();
+        assert!(board.has_sufficient_material(WHITE));
+        assert!(!board.has_winning_material(WHITE));
+
+        let board = parse_fen("4k3/8/8/8/8/8/8/2B1KN2 w - - 0 1").unwrap();
+        assert!(board.has_winning_material(WHITE));
+        assert!(!board.has_winning_material(BLACK));
+    }
+
+    #[test]
+    fn test_draw_reason() {
+        let board = parse_fen("7k/8/4Q3/8/8/8/8/4K3 w - - 0 1").unwrap();
+        match board.play_move(Move::Piece(E6, F7)) {
+            GameResult::Draw(reason) => assert_eq!(reason, DrawReason::Stalemate),
+            _ => panic!("expected stalemate"),
+        }
+
+        let board = parse_fen("4k3/8/8/8/8/8/3r4/3NK3 w - - 0 1").unwrap();
+        match board.play_move(Move::Piece(E1, D2)) {
+            GameResult::Draw(reason) => assert_eq!(reason, DrawReason::DeadPosition),
+            _ => panic!("expected a dead position"),
+        }
+    }
+
+    #[test]
+    fn test_chess960_start_position() {
+        let board = Board::chess960(518).unwrap();
+        assert!(board.is_chess960());
+        assert_eq!(board.set_chess960(false), Board::default());
+
+        let board = Board::chess960(0).unwrap();
+        let back_rank = (0..8)
+            .map(|col| board.get_piece(Position::new(0, col)).unwrap().get_kind())
+            .collect::<Vec<_>>();
+        use PieceKind::*;
+        assert_eq!(
+            back_rank,
+            [Bishop, Bishop, Queen, Knight, Knight, Rook, King, Rook]
+        );
+        assert_eq!(
+            board.get_castling_rights(BLACK).get_queenside_rook_col(),
+            Some(5)
+        );
+        assert_eq!(
+            board.get_castling_rights(BLACK).get_kingside_rook_col(),
+            Some(7)
+        );
+        assert!(Board::chess960(960).is_none());
+    }
+
+    #[test]
+    fn test_chess960_perft() {
+        let board =
+            parse_fen("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9").unwrap();
+        assert_eq!(perft(&board, 1), 21);
+        assert_eq!(perft(&board, 2), 528);
+
+        let board =
+            parse_fen("2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9").unwrap();
+        assert_eq!(perft(&board, 1), 21);
+        assert_eq!(perft(&board, 2), 807);
+    }
+
+    #[test]
+    fn test_chess960_castling() {
+        // king and rook next to each other, with the king on its castled square
+        let board = parse_fen("4k3/8/8/8/8/8/8/5RK1 w F - 0 1").unwrap();
+        assert!(board.can_queenside_castle(WHITE));
+        assert_eq!(board.get_castling_move(G1, F1), Some(Move::QueenSideCastle));
+        assert_eq!(
+            board.get_castling_squares(Move::QueenSideCastle),
+            Some((G1, F1))
+        );
+        let board = match board.play_move(Move::QueenSideCastle) {
+            GameResult::Continuing(board) => board,
+            _ => panic!("castling failed"),
+        };
+        assert_eq!(board.get_piece(C1), Some(Piece::King(WHITE, C1)));
+        assert_eq!(board.get_piece(D1), Some(Piece::Rook(WHITE, D1)));
+        assert!(board.has_no_piece(F1) && board.has_no_piece(G1));
+
+        // the castling rook shields the king's destination from the rook on a1
+        let board = parse_fen("4k3/8/8/8/8/8/8/rR2K3 w B - 0 1").unwrap();
+        assert!(!board.can_queenside_castle(WHITE));
+
+        // regular notation is only understood in regular games
+        let board = Board::default();
+        assert_eq!(board.get_castling_move(E1, G1), Some(Move::KingSideCastle));
+        assert_eq!(
+            board.get_castling_squares(Move::KingSideCastle),
+            Some((E1, G1))
+        );
+        let board = board.set_chess960(true);
+        assert_eq!(board.get_castling_move(E1, G1), None);
+        assert_eq!(board.get_castling_move(E1, H1), Some(Move::KingSideCastle));
+    }
+
+    #[test]
+    fn test_validate() {
+        let invalid = |fen: &str| parse_fen_lenient(fen).unwrap().validate().unwrap_err();
+
+        assert_eq!(Board::default().validate(), Ok(()));
+        assert_eq!(
+            invalid("8/8/8/8/8/8/8/4K3 w - - 0 1"),
+            PositionError::MissingKing(BLACK)
+        );
+        assert_eq!(
+            invalid("4k3/8/8/8/8/8/8/3KK3 w - - 0 1"),
+            PositionError::TooManyKings(WHITE)
+        );
+        assert_eq!(
+            invalid("4k3/8/8/8/8/8/8/P3K3 w - - 0 1"),
+            PositionError::PawnOnBackRank(A1)
+        );
+        assert_eq!(
+            invalid("4k3/pppppppp/p7/8/8/8/8/4K3 w - - 0 1"),
+            PositionError::TooManyPawns(BLACK)
+        );
+        assert_eq!(
+            invalid("4k3/8/8/8/8/8/8/4R1K1 w - - 0 1"),
+            PositionError::OpponentInCheck
+        );
+        assert_eq!(
+            invalid("4k3/8/8/8/8/8/8/4K3 w K - 0 1"),
+            PositionError::InvalidCastlingRights(WHITE)
+        );
+        assert_eq!(
+            invalid("4k3/8/8/8/8/8/8/1R2K3 w A - 0 1"),
+            PositionError::InvalidCastlingRights(WHITE)
+        );
+        assert_eq!(
+            invalid("4k3/8/8/8/8/8/8/4K3 w - e6 0 1"),
+            PositionError::InvalidEnPassant(E6)
+        );
+        assert_eq!(
+            invalid("4k3/8/8/4p3/8/8/8/4K3 b - e6 0 1"),
+            PositionError::InvalidEnPassant(E6)
+        );
+        assert!(parse_fen("4k3/8/8/4p3/8/8/8/4K3 w - e6 0 1").is_ok());
+        assert!(parse_fen("4k3/8/8/8/8/8/8/P3K3 w - - 0 1").is_err());
+
+        // the horde has no king, and pawns on the first rank
+        let board = parse_fen_lenient("4k3/8/8/8/8/8/8/P7 w - - 0 1").unwrap();
+        assert_eq!(board.validate(), Err(PositionError::PawnOnBackRank(A1)));
+        assert_eq!(board.set_variant(Variant::Horde).validate(), Ok(()));
+        assert_eq!(Board::horde().validate(), Ok(()));
+
+        assert_eq!(
+            BoardBuilder::default()
+                .piece(Piece::King(WHITE, E1))
+                .try_build(),
+            Err(PositionError::MissingKing(BLACK))
+        );
+    }
+
+    #[test]
+    fn test_null_move() {
+        let board = parse_fen("4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 1").unwrap();
+        assert_eq!(Move::try_from("0000".to_string()), Ok(Move::Null));
+        assert!(!board.is_legal_move(Move::Null, WHITE));
+        assert_eq!(
+            board.play_move(Move::Null),
+            GameResult::IllegalMove(Move::Null)
+        );
+
+        // passing hands the turn over and forgets the en passant square
+        let passed = board.apply_eval_move(Move::Null);
+        assert_eq!(passed.get_turn_color(), BLACK);
+        assert_eq!(passed.get_en_passant(), None);
+        assert_eq!(passed.get_piece(D5), board.get_piece(D5));
+    }
+
+    #[test]
+    fn test_no_legal_moves() {
+        // checkmate and stalemate leave the search without a move
+        let mate = parse_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+        let stalemate = parse_fen("7k/5Q2/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        for board in [mate, stalemate] {
+            assert_eq!(board.get_best_next_move(2).0, None);
+            assert_eq!(board.get_worst_next_move(2).0, None);
+        }
+        assert!(Board::default().get_best_next_move(1).0.is_some());
+    }
+
+    #[test]
+    fn test_check_move() {
+        let board = Board::default();
+        assert_eq!(board.check_move(Move::Piece(E2, E4)), Ok(()));
+        assert_eq!(
+            board.check_move(Move::Piece(E4, E5)),
+            Err(IllegalMoveReason::NoPieceOnSource(E4))
+        );
+        assert_eq!(
+            board.check_move(Move::Piece(E7, E5)),
+            Err(IllegalMoveReason::NotYourPiece(E7))
+        );
+        assert_eq!(
+            board.check_move(Move::Piece(A1, A3)),
+            Err(IllegalMoveReason::BlockedPath(A2))
+        );
+        assert_eq!(
+            board.check_move(Move::Piece(E2, E5)),
+            Err(IllegalMoveReason::InvalidMovement)
+        );
+        assert_eq!(
+            board.check_move(Move::Promotion(E2, E4, Piece::Queen(WHITE, E4))),
+            Err(IllegalMoveReason::PromotionNotOnLastRank(E4))
+        );
+        assert_eq!(
+            board.check_move(Move::Null),
+            Err(IllegalMoveReason::NullMove)
+        );
+
+        // a bare push to the last rank promotes to a queen
+        let board = parse_fen("4k3/1P6/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        assert_eq!(board.check_move(Move::Piece(B7, B8)), Ok(()));
+        assert_eq!(
+            board.check_move(Move::Promotion(B7, B8, Piece::Knight(WHITE, B8))),
+            Ok(())
+        );
+        assert_eq!(
+            board.check_move(Move::Promotion(B7, B8, Piece::King(WHITE, B8))),
+            Err(IllegalMoveReason::InvalidPromotionPiece(PieceKind::King))
+        );
+        assert_eq!(
+            board.check_move(Move::Promotion(A1, A8, Piece::Queen(WHITE, A8))),
+            Err(IllegalMoveReason::PromotionNotPawn(A1))
+        );
+
+        // the bishop on e2 is pinned to the king by the rook on e8
+        let board = parse_fen("4r1k1/8/8/8/8/8/4B3/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            board.check_move(Move::Piece(E2, D3)),
+            Err(IllegalMoveReason::PiecePinned(E2))
+        );
+        assert_eq!(
+            board.check_move(Move::Piece(E1, E2)),
+            Err(IllegalMoveReason::InvalidMovement)
+        );
+        assert_eq!(board.check_move(Move::Piece(E1, F2)), Ok(()));
+
+        let board = parse_fen("4k3/8/8/8/8/8/8/4K1r1 w - - 0 1").unwrap();
+        assert_eq!(
+            board.check_move(Move::Piece(E1, F1)),
+            Err(IllegalMoveReason::LeavesKingInCheck)
+        );
+
+        // a pawn next to the en passant square can only capture onto it
+        let board = parse_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        assert_eq!(board.check_move(Move::Piece(E5, D6)), Ok(()));
+        for to in [F6, A1, H2, E8] {
+            assert_eq!(
+                board.check_move(Move::Piece(E5, to)),
+                Err(IllegalMoveReason::InvalidMovement),
+                "{}",
+                to
+            );
+        }
+        assert_eq!(board.legal_moves_from(E5).len(), 2);
+
+        // the en passant square is forgotten after one move
+        let board = parse_fen("4k3/8/8/3Pp3/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            board.check_move(Move::Piece(D5, E6)),
+            Err(IllegalMoveReason::EnPassantExpired)
+        );
+
+        let board = parse_fen("r3k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        assert_eq!(board.check_move(Move::KingSideCastle), Ok(()));
+        let board = parse_fen("4k3/8/8/8/8/8/8/R3K2R w Q - 0 1").unwrap();
+        assert_eq!(
+            board.check_move(Move::KingSideCastle),
+            Err(IllegalMoveReason::NoCastlingRights)
+        );
+        let board = parse_fen("4k3/8/8/8/8/8/8/RN2K2R w KQ - 0 1").unwrap();
+        assert_eq!(
+            board.check_move(Move::QueenSideCastle),
+            Err(IllegalMoveReason::BlockedPath(B1))
+        );
+        let board = parse_fen("5rk1/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        assert_eq!(
+            board.check_move(Move::KingSideCastle),
+            Err(IllegalMoveReason::CastlingThroughCheck)
+        );
+    }
+
+    #[test]
+    fn test_attacks_and_pins() {
+        // the rook pins the knight, and the bishop gives check
+        let board = parse_fen("4r2k/8/8/8/1b6/8/4N3/4K3 w - - 0 1").unwrap();
+
+        assert_eq!(board.get_checkers(WHITE), vec![Piece::Bishop(BLACK, B4)]);
+        assert!(board.get_checkers(BLACK).is_empty());
+        assert_eq!(
+            board.get_pins(WHITE),
+            vec![Pin {
+                pinned: Piece::Knight(WHITE, E2),
+                pinner: Piece::Rook(BLACK, E8),
+                ray: vec![E2, E3, E4, E5, E6, E7, E8],
+            }]
+        );
+        assert!(board.get_pins(BLACK).is_empty());
+
+        let board = parse_fen("7k/8/8/7N/8/8/8/4K2R w - - 0 1").unwrap();
+        assert_eq!(
+            board.get_discovered_check_candidates(WHITE),
+            vec![Piece::Knight(WHITE, H5)]
+        );
+        let board = parse_fen("7k/8/8/7N/8/8/8/4K1R1 w - - 0 1").unwrap();
+        assert!(board.get_discovered_check_candidates(WHITE).is_empty());
+
+        // defenders count as attackers of their own pieces
+        let board = Board::default();
+        assert_eq!(
+            board.get_attackers(F3, WHITE),
+            vec![
+                Piece::Pawn(WHITE, E2),
+                Piece::Pawn(WHITE, G2),
+                Piece::Knight(WHITE, G1)
+            ]
+        );
+        assert_eq!(board.get_attackers(D2, WHITE).len(), 4);
+        let counts = board.get_attack_counts(WHITE);
+        let count = |pos| counts[SquareIndex::from_position(pos).unwrap().get_index() as usize];
+        assert_eq!(count(F3), 3);
+        assert_eq!(count(E4), 0);
+        assert_eq!(count(A1), 0);
+
+        // the shortcuts agree with looking at every square and playing
+        // every move out
+        let board =
+            parse_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1").unwrap();
+        for color in [WHITE, BLACK] {
+            let counts = board.get_attack_counts(color);
+            for index in 0..64 {
+                let pos = SquareIndex::new(index).unwrap().to_position();
+                assert_eq!(
+                    counts[index as usize] as usize,
+                    board.get_attackers(pos, color).len()
+                );
+            }
+        }
+        for m in board.get_legal_moves() {
+            assert_eq!(
+                board.gives_check(m),
+                board.apply_move(m).is_in_check(BLACK),
+                "{}",
+                m
+            );
+        }
+    }
+
+    #[test]
+    fn test_perft() {
+        // positions with many checks, pins and evasions
+        let board = parse_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1").unwrap();
+        assert_eq!(perft(&board, 3), 2812);
+        let board =
+            parse_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        assert_eq!(perft(&board, 3), 97862);
+    }
+
+    #[test]
+    fn test_move_info() {
+        let board = Board::default();
+        let mut knight_moves = board.legal_moves_from(G1);
+        knight_moves.sort();
+        assert_eq!(knight_moves, vec![Move::Piece(G1, F3), Move::Piece(G1, H3)]);
+        assert!(board.legal_moves_from(E4).is_empty());
+        assert!(board.legal_moves_from(E7).is_empty());
+        assert_eq!(
+            board.move_from_squares(E2, E4, None),
+            Some(Move::Piece(E2, E4))
+        );
+        assert_eq!(board.move_from_squares(E2, E5, None), None);
+
+        let board = parse_fen("r3k3/1P6/8/3Pp3/8/8/8/R3K2R w KQq e6 0 1").unwrap();
+        assert!(board.legal_moves_from(E1).contains(&Move::KingSideCastle));
+        assert_eq!(
+            board.move_from_squares(E1, G1, None),
+            Some(Move::KingSideCastle)
+        );
+        assert_eq!(
+            board.move_from_squares(E1, C1, None),
+            Some(Move::QueenSideCastle)
+        );
+        assert!(Move::KingSideCastle.is_castle());
+        assert!(!Move::Piece(E1, F1).is_castle());
+
+        // promotions and captures, including en passant
+        let promotion = board.move_from_squares(B7, A8, Some(PieceKind::Knight));
+        assert_eq!(
+            promotion,
+            Some(Move::Promotion(B7, A8, Piece::Knight(WHITE, A8)))
+        );
+        assert!(board.is_promotion(Move::Piece(B7, B8)));
+        assert!(board.is_promotion(promotion.unwrap()));
+        assert!(!board.is_promotion(Move::Piece(D5, D6)));
+        assert_eq!(
+            board.get_captured_piece(Move::Piece(B7, A8)),
+            Some(Piece::Rook(BLACK, A8))
+        );
+        assert_eq!(
+            board.get_captured_piece(Move::Piece(D5, E6)),
+            Some(Piece::Pawn(BLACK, E5))
+        );
+        assert_eq!(board.get_captured_piece(Move::Piece(D5, D6)), None);
+        assert!(board.is_capture(Move::Piece(D5, E6)));
+
+        assert!(board.gives_check(Move::Piece(B7, B8)));
+        assert!(board.gives_check(Move::Piece(A1, A8)));
+        assert!(!board.gives_check(Move::Piece(D5, D6)));
+    }
+
+    #[test]
+    fn test_piece_iterators() {
+        let board = Board::default();
+        assert_eq!(board.pieces().count(), 32);
+        assert_eq!(board.pieces_of(BLACK).count(), 16);
+        assert!(board
+            .pieces_of(BLACK)
+            .all(|piece| piece.get_color() == BLACK));
+        let mut knights: Vec<Piece> = board.pieces_of_kind(WHITE, PieceKind::Knight).collect();
+        knights.sort();
+        assert_eq!(
+            knights,
+            vec![Piece::Knight(WHITE, B1), Piece::Knight(WHITE, G1)]
+        );
+        assert_eq!(board.remove_all(WHITE).pieces_of(WHITE).count(), 0);
+
+        // the kings are tracked as they move, castle, and get captured
+        assert_eq!(board.get_king_pos(WHITE), Some(E1));
+        assert_eq!(board.get_king_pos(BLACK), Some(E8));
+        let board = parse_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        let castled = board.apply_move(Move::KingSideCastle);
+        assert_eq!(castled.get_king_pos(WHITE), Some(G1));
+        assert_eq!(
+            board.apply_move(Move::Piece(E1, D2)).get_king_pos(WHITE),
+            Some(D2)
+        );
+        let board = parse_fen("4k3/4q3/8/8/8/8/8/4QK2 w - - 0 1")
+            .unwrap()
+            .set_variant(Variant::Atomic);
+        let exploded = board.apply_move(Move::Piece(E1, E7));
+        assert_eq!(exploded.get_king_pos(BLACK), None);
+        assert_eq!(exploded.get_king_pos(WHITE), Some(F1));
+        assert_eq!(Board::empty().get_king_pos(WHITE), None);
+        assert_eq!(Board::horde().get_king_pos(WHITE), None);
+    }
+
+    #[test]
+    fn test_transforms() {
+        let board = Board::default();
+        assert_eq!(board.mirror(), board.set_turn(BLACK));
+        assert_eq!(board.mirror().mirror(), board);
+
+        let board =
+            parse_fen("r3k2r/p1pp1pb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPB1PPP/R3K3 b Qkq e3 0 1")
+                .unwrap();
+        let mirrored = board.mirror();
+        assert_eq!(mirrored.get_turn_color(), WHITE);
+        assert_eq!(mirrored.get_piece(E1), Some(Piece::King(WHITE, E1)));
+        assert_eq!(mirrored.get_piece(E8), Some(Piece::King(BLACK, E8)));
+        assert_eq!(mirrored.get_piece(B5), Some(Piece::Pawn(WHITE, B5)));
+        assert_eq!(mirrored.get_en_passant(), Some(E6));
+        assert!(mirrored.get_castling_rights(WHITE).can_kingside_castle());
+        assert!(!mirrored.get_castling_rights(BLACK).can_kingside_castle());
+        assert_eq!(mirrored.mirror(), board);
+        assert_eq!(
+            mirrored.get_legal_moves().len(),
+            board.get_legal_moves().len()
+        );
+
+        let flipped = board.flip_horizontal();
+        assert_eq!(flipped.get_piece(D1), Some(Piece::King(WHITE, D1)));
+        assert_eq!(flipped.get_en_passant(), Some(D3));
+        assert!(!flipped.get_castling_rights(WHITE).can_queenside_castle());
+        assert_eq!(flipped.flip_horizontal().get_piece(E1), board.get_piece(E1));
+
+        let rotated = board.rotate();
+        assert_eq!(rotated.get_piece(D8), Some(Piece::King(BLACK, D8)));
+        assert_eq!(rotated.get_piece(G5), Some(Piece::Pawn(WHITE, G5)));
+    }
+}
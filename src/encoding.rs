@@ -0,0 +1,243 @@
+use crate::{Color, Move, PieceKind, Position};
+
+/// A square on the board, stored as a single index from 0 to 63.
+///
+/// Squares are numbered rank by rank, starting from the white side:
+/// `a1` is 0, `h1` is 7, `a2` is 8, and `h8` is 63.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SquareIndex(u8);
+
+impl SquareIndex {
+    /// Create a square from its index, if it is between 0 and 63.
+    #[inline]
+    pub const fn new(index: u8) -> Option<Self> {
+        if index < 64 {
+            Some(Self(index))
+        } else {
+            None
+        }
+    }
+
+    /// Get the square a position refers to, if it is on the board.
+    #[inline]
+    pub fn from_position(pos: Position) -> Option<Self> {
+        if pos.is_on_board() {
+            Some(Self((pos.get_row() * 8 + pos.get_col()) as u8))
+        } else {
+            None
+        }
+    }
+
+    /// Get the position this square refers to.
+    #[inline]
+    pub const fn to_position(self) -> Position {
+        Position::new((self.0 / 8) as i32, (self.0 % 8) as i32)
+    }
+
+    /// Get the index of this square, from 0 to 63.
+    #[inline]
+    pub const fn get_index(self) -> u8 {
+        self.0
+    }
+}
+
+impl From<SquareIndex> for Position {
+    fn from(square: SquareIndex) -> Self {
+        square.to_position()
+    }
+}
+
+impl core::fmt::Display for SquareIndex {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        write!(f, "{}", self.to_position())
+    }
+}
+
+// the pieces a pawn can promote to, in order of their packed code,
+// including a king for antichess
+const PROMOTIONS: [PieceKind; 5] = [
+    PieceKind::Knight,
+    PieceKind::Bishop,
+    PieceKind::Rook,
+    PieceKind::Queen,
+    PieceKind::King,
+];
+
+// the pieces that can be dropped, in order of their packed code
+const DROPS: [PieceKind; 5] = [
+    PieceKind::Pawn,
+    PieceKind::Knight,
+    PieceKind::Bishop,
+    PieceKind::Rook,
+    PieceKind::Queen,
+];
+
+// the code in the top four bits of a packed move
+const PIECE: u16 = 0;
+const PROMOTION: u16 = 1;
+const DROP: u16 = PROMOTION + PROMOTIONS.len() as u16;
+const KINGSIDE_CASTLE: u16 = DROP + DROPS.len() as u16;
+const QUEENSIDE_CASTLE: u16 = KINGSIDE_CASTLE + 1;
+const NULL: u16 = QUEENSIDE_CASTLE + 1;
+
+/// A move packed into sixteen bits, for keeping long move lists and
+/// search tables small.
+///
+/// From the lowest bit up, a packed move holds
+/// 1. The square the move starts from (six bits)
+/// 2. The square the move ends on, or the square a piece is dropped on
+///    (six bits)
+/// 3. A code for the kind of move, and the kind of piece promoted to or
+///    dropped (four bits)
+///
+/// Converting a `Move` to a packed move and back gives the same move.
+/// The piece a pawn promotes to only keeps its kind, and comes back with
+/// the color of the player promoting, standing on the promotion square,
+/// which is how every move the crate creates holds it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackedMove(u16);
+
+impl PackedMove {
+    /// Pack a move, if all of its squares are on the board.
+    pub fn new(m: Move) -> Option<Self> {
+        let square = |pos| SquareIndex::from_position(pos).map(|s| s.get_index() as u16);
+        let code = |kinds: &[PieceKind], kind| kinds.iter().position(|k| *k == kind);
+        let (code, from, to) = match m {
+            Move::Piece(from, to) => (PIECE, square(from)?, square(to)?),
+            Move::Promotion(from, to, piece) => {
+                let code = PROMOTION + code(&PROMOTIONS, piece.get_kind())? as u16;
+                (code, square(from)?, square(to)?)
+            }
+            Move::Drop(kind, pos) => (DROP + code(&DROPS, kind)? as u16, 0, square(pos)?),
+            Move::KingSideCastle => (KINGSIDE_CASTLE, 0, 0),
+            Move::QueenSideCastle => (QUEENSIDE_CASTLE, 0, 0),
+            Move::Null => (NULL, 0, 0),
+        };
+        Some(Self(code << 12 | to << 6 | from))
+    }
+
+    /// Read a packed move from its bits, if they hold a valid move.
+    pub fn from_bits(bits: u16) -> Option<Self> {
+        let packed = Self(bits);
+        Self::new(packed.unpack()?).filter(|m| *m == packed)
+    }
+
+    /// Get the bits of this packed move.
+    #[inline]
+    pub const fn to_bits(self) -> u16 {
+        self.0
+    }
+
+    /// Get the move this packs.
+    #[inline]
+    pub fn to_move(self) -> Move {
+        self.unpack()
+            .expect("packed moves are only created from valid moves")
+    }
+
+    fn unpack(self) -> Option<Move> {
+        let from = SquareIndex((self.0 & 0x3f) as u8).to_position();
+        let to = SquareIndex((self.0 >> 6 & 0x3f) as u8).to_position();
+        let code = self.0 >> 12;
+        Some(match code {
+            PIECE => Move::Piece(from, to),
+            code if code < DROP => {
+                // only black pawns promote on the first rank
+                let color = if to.get_row() == 0 {
+                    Color::Black
+                } else {
+                    Color::White
+                };
+                let kind = PROMOTIONS[(code - PROMOTION) as usize];
+                Move::Promotion(from, to, kind.with(color, to))
+            }
+            code if code < KINGSIDE_CASTLE => Move::Drop(DROPS[(code - DROP) as usize], to),
+            KINGSIDE_CASTLE => Move::KingSideCastle,
+            QUEENSIDE_CASTLE => Move::QueenSideCastle,
+            NULL => Move::Null,
+            _ => return None,
+        })
+    }
+}
+
+impl From<PackedMove> for Move {
+    fn from(m: PackedMove) -> Self {
+        m.to_move()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        parse_annotated_san_move, parse_fen, parse_san_move, parse_uci_move, Board, Evaluate,
+        Piece, A1, A7, A8, BLACK, E4, H1, H2, H8, WHITE,
+    };
+    use alloc::string::ToString;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn test_square_index() {
+        assert_eq!(
+            SquareIndex::from_position(A1).map(SquareIndex::get_index),
+            Some(0)
+        );
+        assert_eq!(
+            SquareIndex::from_position(H1).map(SquareIndex::get_index),
+            Some(7)
+        );
+        assert_eq!(
+            SquareIndex::from_position(H8).map(SquareIndex::get_index),
+            Some(63)
+        );
+        assert_eq!(SquareIndex::from_position(Position::new(8, 0)), None);
+        assert_eq!(SquareIndex::new(64), None);
+        for index in 0..64 {
+            let square = SquareIndex::new(index).unwrap();
+            assert_eq!(SquareIndex::from_position(square.into()), Some(square));
+        }
+        assert_eq!(SquareIndex::from_position(E4).unwrap().to_string(), "e4");
+    }
+
+    #[test]
+    fn test_packed_move() {
+        let moves = Board::default().get_legal_moves().into_iter().chain([
+            Move::Promotion(A7, A8, Piece::Knight(WHITE, A8)),
+            Move::Promotion(H2, H1, Piece::King(BLACK, H1)),
+            Move::Drop(PieceKind::Pawn, E4),
+            Move::Drop(PieceKind::Queen, H8),
+            Move::KingSideCastle,
+            Move::QueenSideCastle,
+            Move::Null,
+        ]);
+        for m in moves {
+            let packed = PackedMove::new(m).unwrap();
+            assert_eq!(packed.to_move(), m);
+            assert_eq!(PackedMove::from_bits(packed.to_bits()), Some(packed));
+        }
+
+        // promotions parsed from text keep their piece through packing
+        let board = parse_fen("1r2k3/P7/8/8/8/8/7p/4K3 w - - 0 1").unwrap();
+        let parsed = [
+            parse_san_move(&board, "axb8N").unwrap(),
+            parse_san_move(&board, "a8Q").unwrap(),
+            parse_annotated_san_move(&board, "a8=R+").unwrap(),
+            parse_uci_move(&board, "a7b8b").unwrap(),
+            Move::try_from("a7 to a8 knight".to_string()).unwrap(),
+            Move::try_from("h2 to h1 rook".to_string()).unwrap(),
+        ];
+        for m in board.get_legal_moves().into_iter().chain(parsed) {
+            assert_eq!(PackedMove::new(m).unwrap().to_move(), m);
+        }
+
+        assert_eq!(core::mem::size_of::<PackedMove>(), 2);
+        assert_eq!(PackedMove::new(Move::Piece(A1, Position::new(-1, 0))), None);
+        assert_eq!(PackedMove::new(Move::Drop(PieceKind::King, E4)), None);
+        assert_eq!(PackedMove::from_bits(0xffff), None);
+        // null moves never use the square bits
+        assert_eq!(
+            PackedMove::from_bits(PackedMove::new(Move::Null).unwrap().to_bits() | 1),
+            None
+        );
+    }
+}
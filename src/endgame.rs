@@ -0,0 +1,485 @@
+//! Endgame knowledge for positions with little material left.
+//!
+//! Endgames such as king and queen against king are recognized by the
+//! material on the board and evaluated with dedicated rules instead of the
+//! piece-square tables: known wins drive the lone king to the edge, and
+//! endgames that are usually drawn, like opposite colored bishops, have
+//! their evaluation scaled towards zero.
+use crate::board::Board;
+use crate::eval::EvalParams;
+use crate::piece::{Piece, PieceKind};
+use crate::position::Position;
+use crate::Color;
+
+/// The value added for the stronger side in an endgame that is a known win,
+/// so that the search heads for it and then makes progress inside it.
+const KNOWN_WIN: f64 = 1000.0;
+
+/// An endgame with dedicated evaluation knowledge, recognized by the
+/// material left on the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Endgame {
+    /// King and queen against a lone king.
+    Kqk,
+    /// King and rook against a lone king.
+    Krk,
+    /// King, bishop and knight against a lone king.
+    Kbnk,
+    /// King and pawn against a lone king.
+    Kpk,
+    /// King and rook against king and pawn.
+    Krkp,
+    /// Each side has a single bishop, on squares of opposite colors,
+    /// and any number of pawns.
+    OppositeBishops,
+    /// King, bishop and pawns on a single rook file against a lone king,
+    /// where the bishop does not control the promotion square.
+    WrongRookPawn,
+}
+
+// the pieces on the board, counted per color and kind
+struct Material {
+    counts: [[u8; 6]; 2],
+    kings: [Option<Position>; 2],
+    bishops: [Option<Position>; 2],
+    pawns: [Option<Position>; 2],
+    pawn_files: [u8; 2],
+}
+
+impl Material {
+    fn new(board: &Board) -> Self {
+        let mut result = Self {
+            counts: [[0; 6]; 2],
+            kings: [None; 2],
+            bishops: [None; 2],
+            pawns: [None; 2],
+            pawn_files: [0; 2],
+        };
+        for piece in board.pieces() {
+            let side = piece.get_color() as usize;
+            let pos = piece.get_pos();
+            result.counts[side][piece.get_kind() as usize] += 1;
+            match piece {
+                Piece::King(..) => result.kings[side] = Some(pos),
+                Piece::Bishop(..) => result.bishops[side] = Some(pos),
+                Piece::Pawn(..) => {
+                    result.pawns[side] = Some(pos);
+                    result.pawn_files[side] |= 1 << pos.get_col();
+                }
+                _ => {}
+            }
+        }
+        result
+    }
+
+    fn count(&self, color: Color, kind: PieceKind) -> u8 {
+        self.counts[color as usize][kind as usize]
+    }
+
+    fn non_king_count(&self, color: Color) -> u8 {
+        self.counts[color as usize][1..].iter().sum()
+    }
+
+    // does a color have exactly these pieces besides its king?
+    fn only(&self, color: Color, kinds: &[PieceKind]) -> bool {
+        self.non_king_count(color) as usize == kinds.len()
+            && kinds.iter().all(|kind| {
+                self.count(color, *kind) as usize == kinds.iter().filter(|k| *k == kind).count()
+            })
+    }
+}
+
+impl Endgame {
+    /// Recognize the endgame on a board, along with the stronger side.
+    ///
+    /// For `OppositeBishops`, the stronger side is the side with more pawns.
+    pub fn detect(board: &Board) -> Option<(Self, Color)> {
+        Self::detect_material(&Material::new(board))
+    }
+
+    fn detect_material(material: &Material) -> Option<(Self, Color)> {
+        if material.kings[0].is_none() || material.kings[1].is_none() {
+            return None;
+        }
+
+        for strong in [Color::White, Color::Black] {
+            let weak = !strong;
+            if material.non_king_count(weak) == 0 {
+                if material.only(strong, &[PieceKind::Queen]) {
+                    return Some((Self::Kqk, strong));
+                } else if material.only(strong, &[PieceKind::Rook]) {
+                    return Some((Self::Krk, strong));
+                } else if material.only(strong, &[PieceKind::Bishop, PieceKind::Knight]) {
+                    return Some((Self::Kbnk, strong));
+                } else if material.only(strong, &[PieceKind::Pawn]) {
+                    return Some((Self::Kpk, strong));
+                } else if material.count(strong, PieceKind::Bishop) == 1
+                    && material.non_king_count(strong)
+                        == 1 + material.count(strong, PieceKind::Pawn)
+                    && is_wrong_rook_pawn(material, strong)
+                {
+                    return Some((Self::WrongRookPawn, strong));
+                }
+            } else if material.only(strong, &[PieceKind::Rook])
+                && material.only(weak, &[PieceKind::Pawn])
+            {
+                return Some((Self::Krkp, strong));
+            }
+        }
+
+        if let [Some(white_bishop), Some(black_bishop)] = material.bishops {
+            let only_bishop_and_pawns = |color: Color| {
+                material.count(color, PieceKind::Bishop) == 1
+                    && material.non_king_count(color) == 1 + material.count(color, PieceKind::Pawn)
+            };
+            if only_bishop_and_pawns(Color::White)
+                && only_bishop_and_pawns(Color::Black)
+                && is_light_square(white_bishop) != is_light_square(black_bishop)
+            {
+                let strong = if material.count(Color::Black, PieceKind::Pawn)
+                    > material.count(Color::White, PieceKind::Pawn)
+                {
+                    Color::Black
+                } else {
+                    Color::White
+                };
+                return Some((Self::OppositeBishops, strong));
+            }
+        }
+
+        None
+    }
+}
+
+/// Shrink an advantage that the side ahead cannot convert, since a lone
+/// minor piece or two knights cannot force checkmate.
+fn scale_unwinnable(board: &Board, color: Color, value: f64) -> f64 {
+    let ahead = if value > 0.0 { color } else { !color };
+    if board.has_winning_material(ahead) {
+        value
+    } else {
+        value / 8.0
+    }
+}
+
+/// Adjust a color's evaluation of a board with knowledge of the endgame
+/// on the board, if it is recognized. `value` is the regular evaluation.
+pub(crate) fn adjust(board: &Board, color: Color, value: f64, params: &EvalParams) -> f64 {
+    let material = Material::new(board);
+    let (endgame, strong) = match Endgame::detect_material(&material) {
+        Some(endgame) => endgame,
+        None => return scale_unwinnable(board, color, value),
+    };
+    let weak = !strong;
+    let strong_king = material.kings[strong as usize].unwrap_or(Position::new(0, 0));
+    let weak_king = material.kings[weak as usize].unwrap_or(Position::new(0, 0));
+
+    let for_strong = |strong_value: f64| {
+        if color == strong {
+            strong_value
+        } else {
+            -strong_value
+        }
+    };
+
+    match endgame {
+        Endgame::Kqk | Endgame::Krk => {
+            // drive the lone king to the edge, with the king's help
+            let material_value = match endgame {
+                Endgame::Kqk => params.get_material(PieceKind::Queen),
+                _ => params.get_material(PieceKind::Rook),
+            };
+            for_strong(
+                KNOWN_WIN
+                    + material_value
+                    + 10.0 * center_distance(weak_king) as f64
+                    + 5.0 * (7 - distance(strong_king, weak_king)) as f64,
+            )
+        }
+        Endgame::Kbnk => {
+            // only the corners of the bishop's color can be mated in
+            let bishop = material.bishops[strong as usize].unwrap_or(Position::new(0, 0));
+            let corners = if is_light_square(bishop) {
+                [Position::new(0, 7), Position::new(7, 0)]
+            } else {
+                [Position::new(0, 0), Position::new(7, 7)]
+            };
+            let corner_distance = corners
+                .iter()
+                .map(|corner| manhattan_distance(weak_king, *corner))
+                .min()
+                .unwrap_or(0);
+            for_strong(
+                KNOWN_WIN
+                    + params.get_material(PieceKind::Bishop)
+                    + params.get_material(PieceKind::Knight)
+                    + 10.0 * (14 - corner_distance) as f64
+                    + 5.0 * (7 - distance(strong_king, weak_king)) as f64,
+            )
+        }
+        Endgame::Kpk => {
+            let pawn = material.pawns[strong as usize].unwrap_or(Position::new(0, 0));
+            if is_kpk_win(board, strong, strong_king, weak_king, pawn) {
+                for_strong(
+                    KNOWN_WIN
+                        + params.get_material(PieceKind::Pawn)
+                        + 10.0 * relative_row(pawn, strong) as f64
+                        - distance(strong_king, pawn) as f64,
+                )
+            } else {
+                0.0
+            }
+        }
+        Endgame::Krkp => {
+            let pawn = material.pawns[weak as usize].unwrap_or(Position::new(0, 0));
+            for_strong(krkp_value(
+                board,
+                strong,
+                strong_king,
+                weak_king,
+                pawn,
+                params,
+            ))
+        }
+        Endgame::OppositeBishops => {
+            let pawn_difference = (material.count(Color::White, PieceKind::Pawn) as i32
+                - material.count(Color::Black, PieceKind::Pawn) as i32)
+                .abs();
+            if pawn_difference <= 1 {
+                value * 0.25
+            } else {
+                value * 0.5
+            }
+        }
+        Endgame::WrongRookPawn => {
+            let file = material.pawn_files[strong as usize].trailing_zeros() as i32;
+            let promotion = Position::new(relative_last_row(strong), file);
+            if distance(weak_king, promotion) <= 1 {
+                0.0
+            } else {
+                value
+            }
+        }
+    }
+}
+
+fn is_wrong_rook_pawn(material: &Material, strong: Color) -> bool {
+    let files = material.pawn_files[strong as usize];
+    let file = match files {
+        0b0000_0001 => 0,
+        0b1000_0000 => 7,
+        _ => return false,
+    };
+    let promotion = Position::new(relative_last_row(strong), file);
+    match material.bishops[strong as usize] {
+        Some(bishop) => is_light_square(bishop) != is_light_square(promotion),
+        None => false,
+    }
+}
+
+fn is_kpk_win(
+    board: &Board,
+    strong: Color,
+    strong_king: Position,
+    weak_king: Position,
+    pawn: Position,
+) -> bool {
+    let weak_to_move = board.get_turn_color() != strong;
+    let row = relative_row(pawn, strong);
+    let promotion = Position::new(relative_last_row(strong), pawn.get_col());
+
+    // the weak king can take the pawn right away
+    if weak_to_move && distance(weak_king, pawn) == 1 && distance(strong_king, pawn) > 1 {
+        return false;
+    }
+
+    // rule of the square: the pawn promotes before the king catches it
+    let moves_to_promote = 7 - row - if row == 1 { 1 } else { 0 };
+    let strong_king_in_the_way =
+        strong_king.get_col() == pawn.get_col() && relative_row(strong_king, strong) > row;
+    if !strong_king_in_the_way
+        && distance(weak_king, promotion) - if weak_to_move { 1 } else { 0 } > moves_to_promote
+    {
+        return true;
+    }
+
+    let col = pawn.get_col();
+    if col == 0 || col == 7 {
+        // rook pawns are drawn once the weak king reaches the corner
+        return distance(weak_king, promotion) > distance(strong_king, promotion) + 1
+            && distance(weak_king, promotion) > 7 - row;
+    }
+
+    // the strong king stands on one of the pawn's key squares
+    let key_rows = if row <= 3 {
+        row + 2..=row + 2
+    } else {
+        row + 1..=(row + 2).min(7)
+    };
+    key_rows.contains(&relative_row(strong_king, strong))
+        && (strong_king.get_col() - col).abs() <= 1
+}
+
+fn krkp_value(
+    board: &Board,
+    strong: Color,
+    strong_king: Position,
+    weak_king: Position,
+    pawn: Position,
+    params: &EvalParams,
+) -> f64 {
+    let weak = !strong;
+    let strong_to_move = board.get_turn_color() == strong;
+    let promotion = Position::new(relative_last_row(weak), pawn.get_col());
+    let pawn_next = pawn.pawn_up(weak);
+
+    let strong_king_in_front = strong_king.get_col() == pawn.get_col()
+        && relative_row(strong_king, weak) > relative_row(pawn, weak);
+    let rook = params.get_material(PieceKind::Rook);
+
+    if strong_king_in_front
+        || (distance(weak_king, pawn) >= 3 + if strong_to_move { 0 } else { 1 }
+            && distance(weak_king, promotion) >= 3)
+    {
+        // the rook wins the pawn
+        rook - distance(strong_king, pawn) as f64
+    } else if relative_row(weak_king, weak) >= 5
+        && distance(weak_king, pawn) == 1
+        && relative_row(strong_king, weak) <= 4
+        && distance(strong_king, pawn) > 2 + if strong_to_move { 1 } else { 0 }
+    {
+        // the pawn is far advanced and escorted by its king
+        8.0 - distance(strong_king, pawn) as f64
+    } else {
+        20.0 - (distance(strong_king, pawn_next)
+            - distance(weak_king, pawn_next)
+            - distance(pawn, promotion)) as f64
+    }
+}
+
+/// The row of a position from a color's point of view, 0 being its back rank.
+fn relative_row(pos: Position, color: Color) -> i32 {
+    match color {
+        Color::White => pos.get_row(),
+        Color::Black => 7 - pos.get_row(),
+    }
+}
+
+/// The row a color's pawns promote on.
+fn relative_last_row(color: Color) -> i32 {
+    match color {
+        Color::White => 7,
+        Color::Black => 0,
+    }
+}
+
+fn is_light_square(pos: Position) -> bool {
+    (pos.get_row() + pos.get_col()) % 2 == 1
+}
+
+/// The number of king moves between two positions.
+fn distance(a: Position, b: Position) -> i32 {
+    (a.get_row() - b.get_row())
+        .abs()
+        .max((a.get_col() - b.get_col()).abs())
+}
+
+fn manhattan_distance(a: Position, b: Position) -> i32 {
+    (a.get_row() - b.get_row()).abs() + (a.get_col() - b.get_col()).abs()
+}
+
+/// How far a position is from the four center squares, from 0 to 6.
+fn center_distance(pos: Position) -> i32 {
+    let row = pos.get_row();
+    let col = pos.get_col();
+    (3 - row).max(row - 4) + (3 - col).max(col - 4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::DEFAULT_EVAL_PARAMS;
+    use crate::util::parse_fen;
+    use crate::Evaluate;
+
+    fn detect(fen: &str) -> Option<(Endgame, Color)> {
+        Endgame::detect(&parse_fen(fen).unwrap())
+    }
+
+    fn value(fen: &str) -> f64 {
+        parse_fen(fen).unwrap().value_for(Color::White)
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            detect("8/8/8/4k3/8/8/8/3QK3 w - - 0 1"),
+            Some((Endgame::Kqk, Color::White))
+        );
+        assert_eq!(
+            detect("2bnk3/8/8/8/8/8/8/4K3 w - - 0 1"),
+            Some((Endgame::Kbnk, Color::Black))
+        );
+        assert_eq!(
+            detect("4k3/8/8/8/8/8/3p4/R3K3 w - - 0 1"),
+            Some((Endgame::Krkp, Color::White))
+        );
+        assert_eq!(
+            detect("4k3/2b2p2/8/8/8/8/3PB3/4K3 w - - 0 1"),
+            Some((Endgame::OppositeBishops, Color::White))
+        );
+        assert_eq!(detect("4k3/3b1p2/8/8/8/8/3PB3/4K3 w - - 0 1"), None);
+        assert_eq!(
+            detect("k7/8/8/8/8/8/P7/K1B5 w - - 0 1"),
+            Some((Endgame::WrongRookPawn, Color::White))
+        );
+        assert_eq!(detect("k7/8/8/8/8/8/P7/KB6 w - - 0 1"), None);
+        assert_eq!(Endgame::detect(&Board::default()), None);
+    }
+
+    #[test]
+    fn test_mating_endgames() {
+        // the lone king is better off in the center
+        assert!(value("8/8/8/4k3/8/8/8/3QK3 w - - 0 1") > KNOWN_WIN);
+        assert!(value("7k/8/8/8/8/8/8/3QK3 w - - 0 1") > value("8/8/8/4k3/8/8/8/3QK3 w - - 0 1"));
+        assert!(value("8/8/8/4k3/8/8/8/3rK3 w - - 0 1") < -KNOWN_WIN);
+
+        // with a light-squared bishop, only a8 and h1 are mating corners
+        assert!(value("k7/8/8/8/8/8/8/4KBN1 w - - 0 1") > value("7k/8/8/8/8/8/8/4KBN1 w - - 0 1"));
+    }
+
+    #[test]
+    fn test_kpk() {
+        // the king stands on a key square
+        assert!(value("4k3/8/4K3/8/4P3/8/8/8 w - - 0 1") > KNOWN_WIN);
+        // the pawn outruns the king
+        assert!(value("k7/8/8/8/8/8/7P/K7 w - - 0 1") > KNOWN_WIN);
+        // the defending king blocks the pawn
+        assert_eq!(value("8/8/8/4k3/8/4P3/8/4K3 w - - 0 1"), 0.0);
+        // rook pawn with the defending king in the corner
+        assert_eq!(value("k7/8/K7/P7/8/8/8/8 w - - 0 1"), 0.0);
+    }
+
+    #[test]
+    fn test_krkp_material() {
+        // the rook wins the pawn, so the rook's value counts
+        let board = parse_fen("4k3/8/8/8/8/8/3p4/R3K3 w - - 0 1").unwrap();
+        let mut params = DEFAULT_EVAL_PARAMS.clone();
+        let rook = params.get_material(PieceKind::Rook);
+        params.set_material(PieceKind::Rook, rook + 10.0);
+        assert_eq!(
+            params.evaluate(&board, Color::White),
+            DEFAULT_EVAL_PARAMS.evaluate(&board, Color::White) + 10.0
+        );
+    }
+
+    #[test]
+    fn test_draw_scaling() {
+        let board = parse_fen("4k3/2b2p2/8/8/8/8/2PPB3/4K3 w - - 0 1").unwrap();
+        let unscaled = DEFAULT_EVAL_PARAMS.evaluate_pieces(&board, Color::White);
+        assert_eq!(board.value_for(Color::White), unscaled * 0.25);
+
+        assert_eq!(value("k7/8/8/8/8/8/P7/K1B5 w - - 0 1"), 0.0);
+        assert!(value("k7/8/8/8/8/8/P7/KB6 w - - 0 1") > 0.0);
+    }
+}
@@ -0,0 +1,441 @@
+use crate::{Color, PieceKind, Position};
+
+/// The reason a position could never arise in a legal game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PositionError {
+    /// A player has no king, in a variant where they need one.
+    MissingKing(Color),
+    /// A player has more than one king, or a king in Horde's pawn army.
+    TooManyKings(Color),
+    /// A player has more than eight pawns.
+    TooManyPawns(Color),
+    /// A player has more than sixteen pieces.
+    TooManyPieces(Color),
+    /// A pawn stands on the first or last rank.
+    PawnOnBackRank(Position),
+    /// The player who just moved left their own king in check.
+    OpponentInCheck,
+    /// The current player is checked by more than two pieces,
+    /// or is in check where the variant forbids it.
+    ImpossibleCheck,
+    /// A player may castle, but their king or castling rook is not in place.
+    InvalidCastlingRights(Color),
+    /// The en passant square was not just skipped by a double pawn push.
+    InvalidEnPassant(Position),
+}
+
+impl core::fmt::Display for PositionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        match self {
+            Self::MissingKing(color) => write!(f, "{} has no king", color),
+            Self::TooManyKings(color) => write!(f, "{} has too many kings", color),
+            Self::TooManyPawns(color) => write!(f, "{} has too many pawns", color),
+            Self::TooManyPieces(color) => write!(f, "{} has too many pieces", color),
+            Self::PawnOnBackRank(pos) => write!(f, "pawn on the back rank at {}", pos),
+            Self::OpponentInCheck => write!(f, "the side not to move is in check"),
+            Self::ImpossibleCheck => write!(f, "impossible check"),
+            Self::InvalidCastlingRights(color) => {
+                write!(f, "invalid castling rights for {}", color)
+            }
+            Self::InvalidEnPassant(pos) => write!(f, "invalid en passant square {}", pos),
+        }
+    }
+}
+
+impl core::error::Error for PositionError {}
+
+/// The reason a square such as `e4` could not be parsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SquareParseError {
+    /// The square is not a file letter followed by a rank number.
+    WrongLength,
+    /// The file is not one of `a` through `h`.
+    InvalidFile(char),
+    /// The rank is not one of `1` through `8`.
+    InvalidRank(char),
+}
+
+impl core::fmt::Display for SquareParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        match self {
+            Self::WrongLength => write!(f, "a square is a file and a rank, such as `e4`"),
+            Self::InvalidFile(c) => write!(f, "invalid file `{}`", c),
+            Self::InvalidRank(c) => write!(f, "invalid rank `{}`", c),
+        }
+    }
+}
+
+impl core::error::Error for SquareParseError {}
+
+/// The reason a move could not be parsed by `Move::try_from`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MoveParseError {
+    /// One of the squares of the move is invalid.
+    InvalidSquare(SquareParseError),
+    /// The piece to drop is not a piece letter.
+    InvalidDropPiece,
+    /// The piece to promote to is not a knight, bishop, rook or queen.
+    InvalidPromotion,
+    /// The move is not written in any of the supported formats.
+    InvalidFormat,
+}
+
+impl core::fmt::Display for MoveParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        match self {
+            Self::InvalidSquare(e) => write!(f, "{}", e),
+            Self::InvalidDropPiece => write!(f, "invalid piece to drop"),
+            Self::InvalidPromotion => write!(f, "invalid promotion"),
+            Self::InvalidFormat => write!(f, "invalid move format"),
+        }
+    }
+}
+
+impl core::error::Error for MoveParseError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::InvalidSquare(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<SquareParseError> for MoveParseError {
+    fn from(e: SquareParseError) -> Self {
+        Self::InvalidSquare(e)
+    }
+}
+
+/// The reason a FEN string could not be parsed, by the field that failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FenError {
+    /// The FEN does not have four or six space separated fields.
+    WrongFieldCount,
+    /// A piece in the placement field is not a piece letter.
+    InvalidPiece(char),
+    /// A rank in the placement field has more than eight squares.
+    RankTooLong,
+    /// A rank in the placement field has fewer than eight squares.
+    RankTooShort,
+    /// The placement field has fewer than eight ranks.
+    MissingRanks,
+    /// The placement field has more than eight ranks.
+    TooManyRanks,
+    /// A crazyhouse pocket is opened with `[` but never closed.
+    UnclosedPocket,
+    /// A piece in a crazyhouse pocket is not a piece letter, or is a king.
+    InvalidPocketPiece(char),
+    /// A crazyhouse pocket holds more than 255 pieces of one kind.
+    InvalidPocket,
+    /// The active color field is not `w` or `b`.
+    InvalidActiveColor,
+    /// The castling field has a character other than `K`, `Q`, `-` or a file.
+    InvalidCastling(char),
+    /// The en passant field is not `-` or a square.
+    InvalidEnPassant(SquareParseError),
+    /// The halfmove clock is not a number from 0 to 65535.
+    InvalidHalfmoveClock,
+    /// The fullmove number is not a number from 1 to 65535.
+    InvalidFullmoveNumber,
+    /// The fields are well formed, but describe an illegal position.
+    IllegalPosition(PositionError),
+}
+
+impl core::fmt::Display for FenError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        match self {
+            Self::WrongFieldCount => write!(f, "wrong number of fields"),
+            Self::InvalidPiece(c) => write!(f, "placement: unexpected piece `{}`", c),
+            Self::RankTooLong => write!(f, "placement: too many squares in a rank"),
+            Self::RankTooShort => write!(f, "placement: incomplete rank"),
+            Self::MissingRanks => write!(f, "placement: incomplete position"),
+            Self::TooManyRanks => write!(f, "placement: too many ranks"),
+            Self::UnclosedPocket => write!(f, "placement: unclosed pocket"),
+            Self::InvalidPocketPiece(c) => {
+                write!(f, "placement: unexpected piece `{}` in pocket", c)
+            }
+            Self::InvalidPocket => write!(f, "placement: too many pieces in pocket"),
+            Self::InvalidActiveColor => write!(f, "active color: expected `w` or `b`"),
+            Self::InvalidCastling(c) => write!(f, "castling: unexpected character `{}`", c),
+            Self::InvalidEnPassant(e) => write!(f, "en passant: {}", e),
+            Self::InvalidHalfmoveClock => write!(f, "halfmove clock: expected a number"),
+            Self::InvalidFullmoveNumber => {
+                write!(f, "fullmove number: expected a number from 1")
+            }
+            Self::IllegalPosition(e) => write!(f, "illegal position: {}", e),
+        }
+    }
+}
+
+impl core::error::Error for FenError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::InvalidEnPassant(e) => Some(e),
+            Self::IllegalPosition(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<PositionError> for FenError {
+    fn from(e: PositionError) -> Self {
+        Self::IllegalPosition(e)
+    }
+}
+
+/// The reason a move is not legal for the current player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IllegalMoveReason {
+    /// There is no piece on the square the move starts from.
+    NoPieceOnSource(Position),
+    /// The piece on the square the move starts from belongs to the opponent.
+    NotYourPiece(Position),
+    /// The piece cannot move to the destination square.
+    InvalidMovement,
+    /// The piece could move to the destination square, but another piece
+    /// stands in the way.
+    BlockedPath(Position),
+    /// The move would leave the player's king in check.
+    LeavesKingInCheck,
+    /// The piece is pinned to its king, and moving it would expose the king.
+    PiecePinned(Position),
+    /// The king would castle out of, through, or into check.
+    CastlingThroughCheck,
+    /// The player has lost the right to castle on that side, by moving
+    /// their king or castling rook.
+    NoCastlingRights,
+    /// The pawn could only capture en passant right after the enemy pawn
+    /// moved two squares.
+    EnPassantExpired,
+    /// The moving piece promotes, but it is not a pawn.
+    PromotionNotPawn(Position),
+    /// The pawn promotes on a square off its last rank.
+    PromotionNotOnLastRank(Position),
+    /// The pawn cannot promote to that kind of piece.
+    InvalidPromotionPiece(PieceKind),
+    /// The player has no piece of that kind in their pocket.
+    NotInPocket(PieceKind),
+    /// The piece cannot be dropped on that square.
+    InvalidDrop(Position),
+    /// A capture is available, and captures are forced in this variant.
+    CaptureRequired,
+    /// A rule of the variant being played forbids the move.
+    VariantRule,
+    /// A null move only passes the turn in analysis, and is never legal.
+    NullMove,
+}
+
+impl core::fmt::Display for IllegalMoveReason {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        match self {
+            Self::NoPieceOnSource(pos) => write!(f, "no piece on source square {}", pos),
+            Self::NotYourPiece(pos) => write!(f, "the piece on {} is not yours", pos),
+            Self::InvalidMovement => write!(f, "the piece cannot move there"),
+            Self::BlockedPath(pos) => write!(f, "the path is blocked at {}", pos),
+            Self::LeavesKingInCheck => write!(f, "move would leave king in check"),
+            Self::PiecePinned(pos) => write!(f, "the piece on {} is pinned", pos),
+            Self::CastlingThroughCheck => write!(f, "cannot castle out of, through, or into check"),
+            Self::NoCastlingRights => write!(f, "the right to castle on that side was lost"),
+            Self::EnPassantExpired => {
+                write!(f, "en passant is only allowed right after the pawn moved")
+            }
+            Self::PromotionNotPawn(pos) => {
+                write!(f, "the piece on {} is not a pawn and cannot promote", pos)
+            }
+            Self::PromotionNotOnLastRank(pos) => {
+                write!(f, "a pawn cannot promote on {}, off the last rank", pos)
+            }
+            Self::InvalidPromotionPiece(kind) => {
+                write!(f, "a pawn cannot promote to a {}", kind.get_name())
+            }
+            Self::NotInPocket(kind) => write!(f, "no {} in pocket", kind.get_name()),
+            Self::InvalidDrop(pos) => write!(f, "cannot drop on {}", pos),
+            Self::CaptureRequired => write!(f, "a capture is required"),
+            Self::VariantRule => write!(f, "forbidden by the rules of the variant"),
+            Self::NullMove => write!(f, "a null move is never legal"),
+        }
+    }
+}
+
+impl core::error::Error for IllegalMoveReason {}
+
+/// The reason a move in Standard Algebraic Notation could not be played.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SanError {
+    /// The destination square is missing or invalid.
+    InvalidSquare(SquareParseError),
+    /// The crazyhouse drop is not written like `N@f3`.
+    InvalidDrop(MoveParseError),
+    /// No piece of the given kind can move to the destination square.
+    NoMatchingMove,
+    /// More than one piece matches, and the move must say which one moves,
+    /// like `Nexc4` instead of `Nxc4`.
+    AmbiguousMove,
+    /// The move matches a piece, but is illegal.
+    IllegalMove(IllegalMoveReason),
+}
+
+impl core::fmt::Display for SanError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        match self {
+            Self::InvalidSquare(e) => write!(f, "invalid destination: {}", e),
+            Self::InvalidDrop(e) => write!(f, "invalid drop: {}", e),
+            Self::NoMatchingMove => write!(f, "no matching move"),
+            Self::AmbiguousMove => write!(f, "ambiguous move"),
+            Self::IllegalMove(reason) => write!(f, "illegal move: {}", reason),
+        }
+    }
+}
+
+impl core::error::Error for SanError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::InvalidSquare(e) => Some(e),
+            Self::InvalidDrop(e) => Some(e),
+            Self::IllegalMove(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// The reason an EPD record could not be parsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EpdError {
+    /// The first four fields do not describe a legal position.
+    InvalidPosition(FenError),
+    /// A quoted operand is opened with `"` but never closed.
+    UnclosedString,
+    /// An operation has operands, but no opcode.
+    MissingOpcode,
+    /// A move of a `bm` or `am` operation cannot be played in the position.
+    InvalidMove(SanError),
+}
+
+impl core::fmt::Display for EpdError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        match self {
+            Self::InvalidPosition(e) => write!(f, "invalid position: {}", e),
+            Self::UnclosedString => write!(f, "unclosed string"),
+            Self::MissingOpcode => write!(f, "operation without an opcode"),
+            Self::InvalidMove(e) => write!(f, "invalid move: {}", e),
+        }
+    }
+}
+
+impl core::error::Error for EpdError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::InvalidPosition(e) => Some(e),
+            Self::InvalidMove(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// The reason an opening book could not be read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BookError {
+    /// The book is not made of whole 16 byte entries, given its length
+    /// in bytes.
+    InvalidLength(usize),
+}
+
+impl core::fmt::Display for BookError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        match self {
+            Self::InvalidLength(len) => {
+                write!(
+                    f,
+                    "book length of {} bytes is not a whole number of entries",
+                    len
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for BookError {}
+
+/// The reason a game in a PGN file could not be parsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PgnError {
+    /// A tag is not written as `[Name "value"]`.
+    InvalidTag,
+    /// A comment is opened with `{` but never closed.
+    UnclosedComment,
+    /// A variation is opened with `(` but never closed, or closed with `)`
+    /// without being opened.
+    UnclosedVariation,
+    /// The `FEN` tag does not describe a legal position.
+    InvalidPosition(FenError),
+    /// A move cannot be played, given the number of moves before it.
+    InvalidMove(usize, SanError),
+}
+
+impl core::fmt::Display for PgnError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        match self {
+            Self::InvalidTag => write!(f, "invalid tag"),
+            Self::UnclosedComment => write!(f, "unclosed comment"),
+            Self::UnclosedVariation => write!(f, "unbalanced variation"),
+            Self::InvalidPosition(e) => write!(f, "invalid position: {}", e),
+            Self::InvalidMove(ply, e) => write!(f, "invalid move after {} moves: {}", ply, e),
+        }
+    }
+}
+
+impl core::error::Error for PgnError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::InvalidPosition(e) => Some(e),
+            Self::InvalidMove(_, e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// The reason an NNUE network could not be created or loaded.
+#[cfg(feature = "nnue")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NnueError {
+    /// The weight file does not start with the NNUE magic.
+    InvalidMagic,
+    /// The weight file has a version that cannot be read.
+    UnsupportedVersion(u32),
+    /// The hidden layer is empty or too large, given its size.
+    InvalidHiddenSize(usize),
+    /// The number of feature weights is wrong, given the expected and
+    /// found numbers.
+    FeatureWeightCount(usize, usize),
+    /// The number of output weights is wrong, given the expected and found
+    /// numbers.
+    OutputWeightCount(usize, usize),
+    /// The weight file ends before all of the weights.
+    UnexpectedEnd,
+    /// The weight file has bytes after the last weight.
+    TrailingBytes,
+}
+
+#[cfg(feature = "nnue")]
+impl core::fmt::Display for NnueError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        match self {
+            Self::InvalidMagic => write!(f, "not an NNUE weight file"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported weight file version {}", version)
+            }
+            Self::InvalidHiddenSize(hidden) => write!(f, "invalid hidden layer size {}", hidden),
+            Self::FeatureWeightCount(expected, found) => {
+                write!(f, "expected {} feature weights, found {}", expected, found)
+            }
+            Self::OutputWeightCount(expected, found) => {
+                write!(f, "expected {} output weights, found {}", expected, found)
+            }
+            Self::UnexpectedEnd => write!(f, "unexpected end of weight file"),
+            Self::TrailingBytes => write!(f, "trailing bytes after weights"),
+        }
+    }
+}
+
+#[cfg(feature = "nnue")]
+impl core::error::Error for NnueError {}
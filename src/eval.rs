@@ -0,0 +1,416 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+use crate::board::Board;
+use crate::endgame;
+use crate::piece::{Piece, PieceKind};
+use crate::position::Position;
+use crate::variant::Variant;
+use crate::{Color, Evaluate, Move};
+
+const KING_POSITION_WEIGHTS: [[f64; 8]; 8] = [
+    [-3.0, -4.0, -4.0, -5.0, -5.0, -4.0, -4.0, -3.0],
+    [-3.0, -4.0, -4.0, -5.0, -5.0, -4.0, -4.0, -3.0],
+    [-3.0, -4.0, -4.0, -5.0, -5.0, -4.0, -4.0, -3.0],
+    [-3.0, -4.0, -4.0, -5.0, -5.0, -4.0, -4.0, -3.0],
+    [-2.0, -3.0, -3.0, -4.0, -4.0, -3.0, -3.0, -2.0],
+    [-1.0, -2.0, -2.0, -2.0, -2.0, -2.0, -2.0, -1.0],
+    [2.0, 2.0, 0.0, 0.0, 0.0, 0.0, 2.0, 2.0],
+    [2.0, 3.0, 1.0, 0.0, 0.0, 1.0, 3.0, 2.0],
+];
+
+const QUEEN_POSITION_WEIGHTS: [[f64; 8]; 8] = [
+    [-2.0, -1.0, -1.0, -0.5, -0.5, -1.0, -1.0, -2.0],
+    [-1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -1.0],
+    [-1.0, 0.0, 0.5, 0.5, 0.5, 0.5, 0.0, -1.0],
+    [-0.5, 0.0, 0.5, 0.5, 0.5, 0.5, 0.0, -0.5],
+    [0.0, 0.0, 0.5, 0.5, 0.5, 0.5, 0.0, -0.5],
+    [-1.0, 0.5, 0.5, 0.5, 0.5, 0.5, 0.0, -1.0],
+    [-1.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.0, -1.0],
+    [-1.0, -0.0, -1.0, -0.5, -0.5, -0.5, -1.0, -2.0],
+];
+
+const ROOK_POSITION_WEIGHTS: [[f64; 8]; 8] = [
+    [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [0.5, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.5],
+    [-0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -0.5],
+    [-0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -0.5],
+    [-0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -0.5],
+    [-0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -0.5],
+    [-0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -0.5],
+    [0.0, 0.0, 0.0, 0.5, 0.5, 0.0, 0.0, 0.0],
+];
+
+const BISHOP_POSITION_WEIGHTS: [[f64; 8]; 8] = [
+    [-2.0, -1.0, -1.0, -1.0, -1.0, -1.0, -1.0, -2.0],
+    [-1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -1.0],
+    [-1.0, 0.0, 0.5, 1.0, 1.0, 0.5, 0.0, -1.0],
+    [-1.0, 0.5, 0.5, 1.0, 1.0, 0.5, 0.5, -1.0],
+    [-1.0, 0.0, 1.0, 1.0, 1.0, 1.0, 0.0, -1.0],
+    [-1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, -1.0],
+    [-1.0, 0.5, 0.0, 0.0, 0.0, 0.0, 0.5, -1.0],
+    [-2.0, -1.0, -1.0, -1.0, -1.0, -1.0, -1.0, -2.0],
+];
+
+const KNIGHT_POSITION_WEIGHTS: [[f64; 8]; 8] = [
+    [-5.0, -4.0, -3.0, -3.0, -3.0, -3.0, -4.0, -5.0],
+    [-4.0, -2.0, 0.0, 0.0, 0.0, 0.0, -2.0, -4.0],
+    [-3.0, 0.0, 1.0, 1.5, 1.5, 1.0, 0.0, -3.0],
+    [-3.0, 0.5, 1.5, 2.0, 2.0, 1.5, 0.5, -3.0],
+    [-3.0, 0.0, 1.5, 2.0, 2.0, 1.5, 0.0, -3.0],
+    [-3.0, 0.5, 1.0, 1.5, 1.5, 1.0, 0.5, -3.0],
+    [-4.0, -2.0, 0.0, 0.5, 0.5, 0.0, -2.0, -4.0],
+    [-5.0, -4.0, -3.0, -3.0, -3.0, -3.0, -4.0, -5.0],
+];
+
+const PAWN_POSITION_WEIGHTS: [[f64; 8]; 8] = [
+    [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [5.0, 5.0, 5.0, 5.0, 5.0, 5.0, 5.0, 5.0],
+    [1.0, 1.0, 2.0, 3.0, 3.0, 2.0, 1.0, 1.0],
+    [0.5, 0.5, 1.0, 2.5, 2.5, 1.0, 0.5, 0.5],
+    [0.0, 0.0, 0.0, 2.0, 2.0, 0.0, 0.0, 0.0],
+    [0.5, -0.5, -1.0, 0.0, 0.0, -1.0, -0.5, 0.5],
+    [0.5, 1.5, -1.0, -2.0, -2.0, 1.0, 1.5, 0.5],
+    [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+];
+
+/// The built-in evaluation parameters used by `Board::value_for`.
+pub static DEFAULT_EVAL_PARAMS: EvalParams = EvalParams {
+    material: [999990.0, 90.0, 50.0, 30.0, 30.0, 10.0],
+    position_weights: [
+        KING_POSITION_WEIGHTS,
+        QUEEN_POSITION_WEIGHTS,
+        ROOK_POSITION_WEIGHTS,
+        BISHOP_POSITION_WEIGHTS,
+        KNIGHT_POSITION_WEIGHTS,
+        PAWN_POSITION_WEIGHTS,
+    ],
+};
+
+/// The parameters of the static evaluation: a material value and a
+/// piece-square table for every kind of piece.
+///
+/// Piece-square tables are stored from White's point of view, with the
+/// first row being the eighth rank. Black's tables are never stored; they
+/// are derived by mirroring White's tables across the middle of the board,
+/// so both sides are always evaluated symmetrically.
+///
+/// Parameters can be saved with `to_string` and loaded back with `parse`.
+/// The text format is a list of sections, one per piece kind, each made of
+/// the piece's name, its material value, and the 64 entries of its table:
+///
+/// ```text
+/// # lines starting with `#` are comments
+/// pawn 10
+///   0.0  0.0  0.0  0.0  0.0  0.0  0.0  0.0
+///   5.0  5.0  5.0  5.0  5.0  5.0  5.0  5.0
+///   ...
+/// ```
+///
+/// Sections may appear in any order, and pieces that are left out keep
+/// their built-in values.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EvalParams {
+    material: [f64; 6],
+    position_weights: [[[f64; 8]; 8]; 6],
+}
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        DEFAULT_EVAL_PARAMS.clone()
+    }
+}
+
+impl EvalParams {
+    /// Parse evaluation parameters from their text format.
+    pub fn parse(repr: &str) -> Result<Self, String> {
+        let mut result = Self::default();
+        let mut tokens = repr
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or(""))
+            .flat_map(|line| line.split_whitespace());
+
+        while let Some(name) = tokens.next() {
+            let kind = PieceKind::ALL
+                .iter()
+                .find(|kind| kind.get_name() == name)
+                .copied()
+                .ok_or(format!("invalid piece name `{}`", name))?;
+
+            let mut next_number = || -> Result<f64, String> {
+                let token = tokens
+                    .next()
+                    .ok_or(format!("missing values for `{}`", name))?;
+                token
+                    .parse::<f64>()
+                    .map_err(|_| format!("invalid number `{}` for `{}`", token, name))
+            };
+
+            result.material[kind.index()] = next_number()?;
+            for row in 0..8 {
+                for col in 0..8 {
+                    result.position_weights[kind.index()][row][col] = next_number()?;
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Get the material value of a kind of piece.
+    #[inline]
+    pub fn get_material(&self, kind: PieceKind) -> f64 {
+        self.material[kind.index()]
+    }
+
+    /// Set the material value of a kind of piece.
+    #[inline]
+    pub fn set_material(&mut self, kind: PieceKind, value: f64) {
+        self.material[kind.index()] = value
+    }
+
+    /// Get the positional weight of a kind of piece on a given square,
+    /// from the point of view of the piece's color.
+    #[inline]
+    pub fn get_position_weight(&self, kind: PieceKind, color: Color, pos: Position) -> f64 {
+        let (row, col) = Self::table_index(color, pos);
+        self.position_weights[kind.index()][row][col]
+    }
+
+    /// Set the positional weight of a kind of piece on a given square,
+    /// from White's point of view. The weight of the mirrored square for
+    /// Black changes along with it.
+    #[inline]
+    pub fn set_position_weight(&mut self, kind: PieceKind, pos: Position, value: f64) {
+        let (row, col) = Self::table_index(Color::White, pos);
+        self.position_weights[kind.index()][row][col] = value
+    }
+
+    #[inline]
+    fn table_index(color: Color, pos: Position) -> (usize, usize) {
+        let row = match color {
+            Color::White => 7 - pos.get_row(),
+            Color::Black => pos.get_row(),
+        };
+        (row as usize, pos.get_col() as usize)
+    }
+
+    /// Get the weighted value of a piece: its material value plus the
+    /// positional weight of the square it sits on.
+    #[inline]
+    pub fn weighted_value(&self, piece: &Piece) -> f64 {
+        self.get_position_weight(piece.get_kind(), piece.get_color(), piece.get_pos())
+            + self.get_material(piece.get_kind())
+    }
+
+    /// Get the value of a board for a given color using these parameters.
+    /// This subtracts the opponents value, and accounts for piece positions
+    /// and material value.
+    ///
+    /// Recognized endgames are evaluated with dedicated knowledge instead,
+    /// or scaled towards a draw (see `Endgame`).
+    pub fn evaluate(&self, board: &Board, color: Color) -> f64 {
+        let value = self.evaluate_pieces(board, color);
+        match board.get_variant() {
+            // the endgame knowledge only holds for regular chess
+            Variant::Standard => endgame::adjust(board, color, value, self),
+            variant => variant.adjust(board, color, value, self),
+        }
+    }
+
+    /// The sum of the weighted values of every piece, without any endgame
+    /// knowledge.
+    pub(crate) fn evaluate_pieces(&self, board: &Board, color: Color) -> f64 {
+        let mut result = 0.0;
+        for row in 0..8 {
+            for col in 0..8 {
+                if let Some(piece) = board.get_piece(Position::new(row, col)) {
+                    if piece.get_color() == color {
+                        result += self.weighted_value(&piece);
+                    } else {
+                        result -= self.weighted_value(&piece);
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Wrap a board so that it is evaluated and searched with these
+    /// parameters instead of the built-in ones.
+    #[inline]
+    pub fn with_board(&self, board: Board) -> ParamBoard<'_> {
+        ParamBoard {
+            board,
+            params: self,
+        }
+    }
+}
+
+impl FromStr for EvalParams {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl core::fmt::Display for EvalParams {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        writeln!(f, "# chess-engine evaluation parameters")?;
+        writeln!(
+            f,
+            "# piece-square tables are from White's view, rank 8 first"
+        )?;
+        for kind in PieceKind::ALL {
+            writeln!(f, "{} {}", kind.get_name(), self.get_material(kind))?;
+            for row in &self.position_weights[kind.index()] {
+                let row = row.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+                writeln!(f, "  {}", row.join(" "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A board that is evaluated with a custom set of `EvalParams`.
+///
+/// This can be used anywhere a `Board` can be searched, such as
+/// `get_best_next_move`.
+#[derive(Clone, Copy, Debug)]
+pub struct ParamBoard<'a> {
+    board: Board,
+    params: &'a EvalParams,
+}
+
+impl<'a> ParamBoard<'a> {
+    /// Get the board being evaluated.
+    #[inline]
+    pub fn get_board(&self) -> &Board {
+        &self.board
+    }
+}
+
+impl<'a> Evaluate for ParamBoard<'a> {
+    #[inline]
+    fn value_for(&self, color: Color) -> f64 {
+        self.params.evaluate(&self.board, color)
+    }
+
+    #[inline]
+    fn get_current_player_color(&self) -> Color {
+        self.board.get_current_player_color()
+    }
+
+    #[inline]
+    fn get_legal_moves(&self) -> Vec<Move> {
+        self.board.get_legal_moves()
+    }
+
+    #[inline]
+    fn get_no_moves_value(&self, color: Color) -> f64 {
+        self.board.get_no_moves_value(color)
+    }
+
+    #[inline]
+    fn apply_eval_move(&self, m: Move) -> Self {
+        Self {
+            board: self.board.apply_eval_move(m),
+            params: self.params,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::*;
+    use crate::util::parse_fen;
+
+    #[test]
+    fn test_default_round_trip() {
+        let params = EvalParams::default();
+        let text = params.to_string();
+        assert_eq!(EvalParams::parse(&text).unwrap(), params);
+    }
+
+    #[test]
+    fn test_partial_params() {
+        assert!(EvalParams::parse("# only knights\nknight 31\n").is_err());
+        assert!(EvalParams::parse("dragon 5").is_err());
+
+        let mut text = String::from("knight 31\n");
+        for _ in 0..64 {
+            text.push_str("1 ");
+        }
+        let params = EvalParams::parse(&text).unwrap();
+        assert_eq!(params.get_material(PieceKind::Knight), 31.0);
+        assert_eq!(params.get_material(PieceKind::Queen), 90.0);
+        assert_eq!(
+            params.weighted_value(&Piece::Knight(Color::Black, A1)),
+            32.0
+        );
+    }
+
+    #[test]
+    fn test_black_tables_are_mirrored() {
+        let params = EvalParams::default();
+        for kind in PieceKind::ALL {
+            for row in 0..8 {
+                for col in 0..8 {
+                    let pos = Position::new(row, col);
+                    let mirrored = Position::new(7 - row, col);
+                    assert_eq!(
+                        params.get_position_weight(kind, Color::White, pos),
+                        params.get_position_weight(kind, Color::Black, mirrored)
+                    );
+                }
+            }
+        }
+
+        assert_eq!(Piece::Pawn(Color::White, E4).get_weighted_value(), 12.0);
+        assert_eq!(Piece::Pawn(Color::Black, E5).get_weighted_value(), 12.0);
+        assert_eq!(Board::default().value_for(Color::White), 0.0);
+    }
+
+    #[test]
+    fn test_param_board_search() {
+        let mut params = EvalParams::default();
+        params.set_material(PieceKind::Pawn, 0.0);
+        let board = params.with_board(Board::default());
+        assert_eq!(
+            board.value_for(Color::White),
+            params.evaluate(&Board::default(), Color::White)
+        );
+        let (m, _, _) = board.get_best_next_move(1);
+        assert!(board.get_board().is_legal_move(m.unwrap(), Color::White));
+    }
+
+    #[test]
+    fn test_mirrored_evaluation() {
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+        ];
+        let params = EvalParams::default();
+        for fen in fens {
+            let board = parse_fen(fen).unwrap();
+            let mirrored = board.mirror();
+            assert_eq!(
+                board.value_for(Color::White),
+                mirrored.value_for(Color::Black)
+            );
+            assert_eq!(
+                board.value_for(Color::Black),
+                mirrored.value_for(Color::White)
+            );
+            assert_eq!(
+                params.evaluate(&board, Color::White),
+                params.evaluate(&mirrored, Color::Black)
+            );
+        }
+    }
+}
@@ -2,7 +2,7 @@ use alloc::string::String;
 
 use crate::board::Board;
 use crate::util::{format_fen, parse_fen, parse_san_move};
-use crate::{Color, GameResult};
+use crate::{Color, DrawReason, FenError, GameResult, IllegalMoveReason, SanError, Variant};
 
 pub enum GameAction {
     // accept draw if previous action was OfferDraw
@@ -29,9 +29,37 @@ pub enum GameError {
     // no more actions allowed once game is over
     GameAlreadyOver,
     // unable to parse move for current turn
-    InvalidMove,
+    InvalidMove(SanError),
+    // move was parsed, but is not legal for current turn
+    IllegalMove(IllegalMoveReason),
+    // accepting a draw the opponent did not offer
+    NoDrawOffered,
     // unable to parse position
-    InvalidPosition,
+    InvalidPosition(FenError),
+}
+
+impl core::fmt::Display for GameError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        match self {
+            Self::AmbiguousMove => write!(f, "ambiguous move"),
+            Self::GameAlreadyOver => write!(f, "the game is already over"),
+            Self::InvalidMove(e) => write!(f, "invalid move: {}", e),
+            Self::IllegalMove(reason) => write!(f, "illegal move: {}", reason),
+            Self::NoDrawOffered => write!(f, "no draw was offered"),
+            Self::InvalidPosition(e) => write!(f, "invalid position: {}", e),
+        }
+    }
+}
+
+impl core::error::Error for GameError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::InvalidMove(e) => Some(e),
+            Self::IllegalMove(e) => Some(e),
+            Self::InvalidPosition(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -41,7 +69,12 @@ pub enum GameOver {
     BlackCheckmates,
     BlackResigns,
     Stalemate,
+    DeadPosition,
     DrawAccepted,
+    // won or drawn by a rule of a variant, rather than by checkmate
+    WhiteWins,
+    BlackWins,
+    VariantDraw,
 }
 
 // wrapper around chess_engine::Board
@@ -61,12 +94,7 @@ impl Game {
         draw_offered: Option<Color>,
         status: Option<GameOver>,
     ) -> Result<Self, GameError> {
-        let board = match parse_fen(fen) {
-            Ok(board) => board,
-            Err(_) => {
-                return Err(GameError::InvalidPosition);
-            }
-        };
+        let board = parse_fen(fen).map_err(GameError::InvalidPosition)?;
         Ok(Game {
             board,
             draw_offered,
@@ -74,8 +102,8 @@ impl Game {
         })
     }
 
-    pub fn to_fen(&self, halfmove_clock: u8, fullmove_number: u8) -> Result<String, String> {
-        format_fen(&self.board, halfmove_clock, fullmove_number)
+    pub fn to_fen(&self) -> Result<String, FenError> {
+        format_fen(&self.board)
     }
 
     // convenience accessor for board.get_turn_color
@@ -104,7 +132,7 @@ impl Game {
                 return Ok(&self.status);
             }
         }
-        Err(GameError::InvalidMove {})
+        Err(GameError::NoDrawOffered)
     }
 
     // move a piece and optionally offer a draw
@@ -115,9 +143,9 @@ impl Game {
     ) -> Result<&Option<GameOver>, GameError> {
         let chess_move = match parse_san_move(&self.board, movestr) {
             Ok(chess_move) => chess_move,
-            Err(_) => {
-                return Err(GameError::InvalidMove {});
-            }
+            Err(SanError::AmbiguousMove) => return Err(GameError::AmbiguousMove),
+            Err(SanError::IllegalMove(reason)) => return Err(GameError::IllegalMove(reason)),
+            Err(e) => return Err(GameError::InvalidMove(e)),
         };
 
         self.draw_offered = match draw_offered {
@@ -129,13 +157,20 @@ impl Game {
                 self.board = board;
                 None
             }
-            GameResult::IllegalMove(_) => {
-                return Err(GameError::InvalidMove {});
+            GameResult::IllegalMove(m) => {
+                let reason = self.board.check_move(m).err();
+                return Err(GameError::IllegalMove(
+                    reason.unwrap_or(IllegalMoveReason::InvalidMovement),
+                ));
             }
-            GameResult::Stalemate => Some(GameOver::Stalemate),
-            GameResult::Victory(color) => match color {
-                Color::Black => Some(GameOver::BlackCheckmates),
-                Color::White => Some(GameOver::WhiteCheckmates),
+            GameResult::Draw(DrawReason::Stalemate) => Some(GameOver::Stalemate),
+            GameResult::Draw(DrawReason::DeadPosition) => Some(GameOver::DeadPosition),
+            GameResult::Draw(DrawReason::VariantRule) => Some(GameOver::VariantDraw),
+            GameResult::Victory(color) => match (color, self.board.get_variant()) {
+                (Color::Black, Variant::Standard) => Some(GameOver::BlackCheckmates),
+                (Color::White, Variant::Standard) => Some(GameOver::WhiteCheckmates),
+                (Color::Black, _) => Some(GameOver::BlackWins),
+                (Color::White, _) => Some(GameOver::WhiteWins),
             },
         };
         Ok(&self.status)
@@ -1,6 +1,8 @@
 #![no_std]
 #[macro_use]
 extern crate alloc;
+#[cfg(feature = "syzygy")]
+extern crate std;
 use alloc::{
     string::{String, ToString},
     vec::Vec,
@@ -9,7 +11,7 @@ use alloc::{
 use core::convert::TryFrom;
 
 mod board;
-pub use board::{Board, BoardBuilder};
+pub use board::{Board, BoardBuilder, Pin};
 
 mod game;
 pub use game::{Game, GameAction, GameError, GameOver};
@@ -18,7 +20,19 @@ mod square;
 pub use square::{Square, EMPTY_SQUARE};
 
 mod piece;
-pub use piece::Piece;
+pub use piece::{Piece, PieceKind};
+
+mod eval;
+pub use eval::{EvalParams, ParamBoard, DEFAULT_EVAL_PARAMS};
+
+mod endgame;
+pub use endgame::Endgame;
+
+mod variant;
+pub use variant::Variant;
+
+#[cfg(feature = "nnue")]
+pub mod nnue;
 
 mod position;
 pub use position::*;
@@ -26,10 +40,28 @@ pub use position::*;
 mod util;
 pub use util::*;
 
+mod error;
+pub use error::*;
+
+mod encoding;
+pub use encoding::{PackedMove, SquareIndex};
+
+mod polyglot;
+pub use polyglot::{polyglot_key, Book, BookBuilder, BookEntry, BookSelection};
+
+mod tablebase;
+pub use tablebase::{Tablebase, TablebaseBoard, Wdl};
+
+#[cfg(feature = "syzygy")]
+mod syzygy;
+#[cfg(feature = "syzygy")]
+pub use syzygy::Syzygy;
+
 pub const WHITE: Color = Color::White;
 pub const BLACK: Color = Color::Black;
 
 /// The result of a move being played on the board.
+#[allow(clippy::large_enum_variant)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum GameResult {
     /// The game is not finished, and the game is still in play.
@@ -37,21 +69,8 @@ pub enum GameResult {
     /// One player, the victor, checkmated the other.
     /// This stores the color of the winner.
     Victory(Color),
-    /// The game is drawn. This can be a result of the current player
-    /// having no legal moves and not being in check, or because
-    /// both players have insufficient material on the board.
-    ///
-    /// Insufficient material consists of:
-    /// 1. The player only has a king
-    /// 2. The player only has a king and a knight
-    /// 3. The player only has a king and two knights
-    /// 4. The player only has a king and a bishop
-    /// 5. The player only has a king and two bishops
-    ///
-    /// In a regular game of chess, threefold repetition also triggers
-    /// a stalemate, but this engine does not have builtin support for
-    /// threefold repetition detection yet.
-    Stalemate,
+    /// The game is drawn, for the stored reason.
+    Draw(DrawReason),
     /// An illegal move was made. This can include many things,
     /// such as moving a piece through another piece, attempting
     /// to capture an allied piece, moving non-orthogonally or
@@ -62,6 +81,38 @@ pub enum GameResult {
     IllegalMove(Move),
 }
 
+/// The rule that ended a game in a draw.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DrawReason {
+    /// The current player has no legal moves, and is not in check.
+    Stalemate,
+    /// Neither player can checkmate the other by any sequence of legal moves,
+    /// such as king against king, king and minor piece against king, or
+    /// kings with bishops that all stand on squares of the same color.
+    ///
+    /// In a regular game of chess, threefold repetition and the fifty move
+    /// rule also draw the game, but this engine does not have builtin
+    /// support for them yet.
+    DeadPosition,
+    /// A rule of the variant being played drew the game, such as both
+    /// kings reaching the eighth rank in Racing Kings.
+    VariantRule,
+}
+
+impl core::fmt::Display for DrawReason {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Stalemate => "stalemate",
+                Self::DeadPosition => "dead position",
+                Self::VariantRule => "variant rule",
+            }
+        )
+    }
+}
+
 /// The color of a piece.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Color {
@@ -109,6 +160,9 @@ pub enum Move {
     /// 3. The square adjacent to the king on the respective side is not threatened by an enemy piece
     ///
     /// If all of these conditions are satisfied, castling is a legal move
+    ///
+    /// In Chess960, the king and rook end up on the same squares as in
+    /// regular chess, wherever they started from.
     QueenSideCastle,
     /// If the current player is white, move the king to the G1 square, and the kingside rook to
     /// the F1 square. If the current player is black, however, move the king to the G8 square,
@@ -128,15 +182,23 @@ pub enum Move {
     /// en-passant square is forgotten and can no longer be used.
     Piece(Position, Position),
     Promotion(Position, Position, Piece),
-    /// When played by another player, it awards victory to the other.
-    Resign,
+    /// Drop a piece of the given kind from the current player's pocket
+    /// onto an empty square, in crazyhouse.
+    ///
+    /// Pawns cannot be dropped on the first or last rank.
+    Drop(PieceKind, Position),
+    /// Pass the turn to the other player without moving a piece.
+    ///
+    /// This is never legal in a game, but it lets the search and analysis
+    /// tools ask what the opponent could do if it were their turn.
+    Null,
 }
 
 /// Try to parse a Move from a string.
 ///
 /// Possible valid formats include:
-/// - `"resign"`
-/// - `"resigns"`
+/// - `"null"`
+/// - `"0000"` (null move, in UCI notation)
 /// - `"castle queenside"`
 /// - `"O-O-O"` (correct notation)
 /// - `"o-o-o"` (incorrect notation, but will accept)
@@ -148,16 +210,17 @@ pub enum Move {
 /// - `"e2e4"`
 /// - `"e2 e4"`
 /// - `"e2 to e4"`
+/// - `"N@f3"` (crazyhouse drop, `"@f3"` drops a pawn)
 ///
 /// Parsing a move such as `"knight to e4"` or `"Qxe4"` will NOT work.
 impl TryFrom<String> for Move {
-    type Error = String;
+    type Error = MoveParseError;
 
     fn try_from(repr: String) -> Result<Self, Self::Error> {
         let repr = repr.trim().to_string();
 
         Ok(match repr.as_str() {
-            "resign" | "resigns" => Self::Resign,
+            "null" | "0000" => Self::Null,
             "queenside castle" | "castle queenside" | "O-O-O" | "0-0-0" | "o-o-o" => {
                 Self::QueenSideCastle
             }
@@ -165,6 +228,21 @@ impl TryFrom<String> for Move {
             other => {
                 let words = other.split_whitespace().collect::<Vec<&str>>();
 
+                if let [drop] = words[..] {
+                    if let Some((kind, pos)) = drop.split_once('@') {
+                        let kind = match kind {
+                            "" => PieceKind::Pawn,
+                            kind => kind
+                                .chars()
+                                .next()
+                                .and_then(PieceKind::from_letter)
+                                .filter(|_| kind.len() == 1)
+                                .ok_or(MoveParseError::InvalidDropPiece)?,
+                        };
+                        return Ok(Self::Drop(kind, Position::pgn(pos)?));
+                    }
+                }
+
                 if words.len() == 1 && words[0].len() == 4 {
                     Self::Piece(
                         Position::pgn(&words[0][..2])?,
@@ -175,13 +253,22 @@ impl TryFrom<String> for Move {
                 } else if words.len() == 3 && words[1] == "to" {
                     Self::Piece(Position::pgn(words[0])?, Position::pgn(words[2])?)
                 } else if words.len() == 4 && words[1] == "to" {
-                    let piece = Piece::try_from(words[3])?;
+                    let piece = Piece::try_from(words[3])
+                        .map_err(|_| MoveParseError::InvalidPromotion)?;
                     if piece.is_king() || piece.is_pawn() {
-                        return Err(String::from("invalid promotion"));
+                        return Err(MoveParseError::InvalidPromotion);
                     }
-                    Self::Promotion(Position::pgn(words[0])?, Position::pgn(words[2])?, piece)
+                    // the piece name has no color, so take it from the rank
+                    // the pawn promotes on, as only black promotes on the first
+                    let to = Position::pgn(words[2])?;
+                    let color = if to.get_row() == 0 {
+                        Color::Black
+                    } else {
+                        Color::White
+                    };
+                    Self::Promotion(Position::pgn(words[0])?, to, piece.get_kind().with(color, to))
                 } else {
-                    return Err(format!("invalid move format `{}`", other));
+                    return Err(MoveParseError::InvalidFormat);
                 }
             }
         })
@@ -189,11 +276,17 @@ impl TryFrom<String> for Move {
 }
 
 impl Move {
+    /// Is this move castling, on either side?
+    #[inline]
+    pub fn is_castle(&self) -> bool {
+        matches!(self, Self::KingSideCastle | Self::QueenSideCastle)
+    }
+
     /// Try to parse a Move from a string.
     ///
     /// Possible valid formats include:
-    /// - `"resign"`
-    /// - `"resigns"`
+    /// - `"null"`
+    /// - `"0000"` (null move, in UCI notation)
     /// - `"castle queenside"`
     /// - `"O-O-O"` (correct notation)
     /// - `"o-o-o"` (incorrect notation, but will accept)
@@ -205,9 +298,10 @@ impl Move {
     /// - `"e2e4"`
     /// - `"e2 e4"`
     /// - `"e2 to e4"`
+    /// - `"N@f3"` (crazyhouse drop, `"@f3"` drops a pawn)
     ///
     /// Parsing a move such as `"knight to e4"` or `"Qxe4"` will NOT work.
-    pub fn parse(repr: String) -> Result<Self, String> {
+    pub fn parse(repr: String) -> Result<Self, MoveParseError> {
         Self::try_from(repr)
     }
 }
@@ -222,7 +316,8 @@ impl core::fmt::Display for Move {
             }
             Move::KingSideCastle => write!(f, "O-O"),
             Move::QueenSideCastle => write!(f, "O-O-O"),
-            Move::Resign => write!(f, "Resign"),
+            Move::Drop(kind, pos) => write!(f, "{}@{}", kind.get_letter(), pos),
+            Move::Null => write!(f, "null"),
         }
     }
 }
@@ -243,20 +338,30 @@ pub trait Evaluate: Sized {
     /// Apply a move to the board for evaluation.
     fn apply_eval_move(&self, m: Move) -> Self;
 
+    /// Get the value of a position where the current player has no legal
+    /// moves, for a given color. By default, the current player has lost.
+    fn get_no_moves_value(&self, color: Color) -> f64 {
+        if color == self.get_current_player_color() {
+            -999999.0
+        } else {
+            999999.0
+        }
+    }
+
     /// Get the best move for the current player with `depth` number of moves
     /// of lookahead.
     ///
     /// This method returns
-    /// 1. The best move
+    /// 1. The best move, or `None` if the current player has no legal moves
     /// 2. The number of boards evaluated to come to a conclusion
     /// 3. The rating of the best move
     ///
     /// It's best not to use the rating value by itself for anything, as it
     /// is relative to the other player's move ratings as well.
-    fn get_best_next_move(&self, depth: i32) -> (Move, u64, f64) {
+    fn get_best_next_move(&self, depth: i32) -> (Option<Move>, u64, f64) {
         let legal_moves = self.get_legal_moves();
         let mut best_move_value = -999999.0;
-        let mut best_move = Move::Resign;
+        let mut best_move = None;
 
         let color = self.get_current_player_color();
 
@@ -271,7 +376,7 @@ pub trait Evaluate: Sized {
                 &mut board_count,
             );
             if child_board_value >= best_move_value {
-                best_move = *m;
+                best_move = Some(*m);
                 best_move_value = child_board_value;
             }
         }
@@ -283,16 +388,16 @@ pub trait Evaluate: Sized {
     /// of lookahead.
     ///
     /// This method returns
-    /// 1. The best move
+    /// 1. The best move, or `None` if the current player has no legal moves
     /// 2. The number of boards evaluated to come to a conclusion
     /// 3. The rating of the best move
     ///
     /// It's best not to use the rating value by itself for anything, as it
     /// is relative to the other player's move ratings as well.
-    fn get_worst_next_move(&self, depth: i32) -> (Move, u64, f64) {
+    fn get_worst_next_move(&self, depth: i32) -> (Option<Move>, u64, f64) {
         let legal_moves = self.get_legal_moves();
         let mut best_move_value = -999999.0;
-        let mut best_move = Move::Resign;
+        let mut best_move = None;
 
         let color = self.get_current_player_color();
 
@@ -308,7 +413,7 @@ pub trait Evaluate: Sized {
             );
 
             if child_board_value >= best_move_value {
-                best_move = *m;
+                best_move = Some(*m);
                 best_move_value = child_board_value;
             }
         }
@@ -339,6 +444,9 @@ pub trait Evaluate: Sized {
         }
 
         let legal_moves = self.get_legal_moves();
+        if legal_moves.is_empty() {
+            return self.get_no_moves_value(getting_move_for);
+        }
         let mut best_move_value;
 
         if is_maximizing {
@@ -0,0 +1,593 @@
+//! An efficiently updatable neural network (NNUE) evaluator.
+//!
+//! The network uses the simple "768" architecture: every (color, kind,
+//! square) combination is one input feature, seen from both sides'
+//! perspectives. Each perspective has its own accumulator holding the
+//! first layer's output, which is updated incrementally as pieces move
+//! instead of being recomputed from scratch. The two accumulators go
+//! through a clipped ReLU, the side to move's first, into a single output.
+//!
+//! ```text
+//! 768 inputs -> N hidden (x2 perspectives) -> 1 output
+//! ```
+//!
+//! # Weight file format
+//!
+//! All values are little-endian.
+//!
+//! | Field | Type | Count |
+//! |-|-|-|
+//! | magic `b"CENN"` | `u8` | 4 |
+//! | version (`1`) | `u32` | 1 |
+//! | hidden size `N` | `u32` | 1 |
+//! | feature weights | `i16` | 768 × N |
+//! | feature biases | `i16` | N |
+//! | output weights | `i16` | 2 × N |
+//! | output bias | `i32` | 1 |
+//!
+//! Feature weights are stored feature by feature, so the `N` weights of
+//! feature `i` start at `i * N`. A feature index is
+//! `side * 384 + kind * 64 + square`, where `side` is 0 for the
+//! perspective's own pieces and 1 for the opponent's, `kind` follows
+//! `PieceKind::ALL` (king first, pawn last), and `square` is
+//! `rank * 8 + file` with a1 = 0. Black's perspective flips the board
+//! vertically, so both sides see their own pieces starting on rank 1.
+//!
+//! The first layer is quantized by `QA` (255) and the output weights by
+//! `QB` (64). The output is scaled to centipawns by
+//! `output * 400 / (QA * QB)`.
+use alloc::vec::Vec;
+
+use crate::board::Board;
+use crate::error::NnueError;
+use crate::piece::Piece;
+use crate::position::Position;
+use crate::variant::Variant;
+use crate::{Color, Evaluate, Move};
+
+const MAGIC: &[u8; 4] = b"CENN";
+const VERSION: u32 = 1;
+const INPUTS: usize = 768;
+const QA: i32 = 255;
+const QB: i32 = 64;
+const SCALE: i32 = 400;
+
+/// The weights of an NNUE network.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Network {
+    hidden: usize,
+    feature_weights: Vec<i16>,
+    feature_biases: Vec<i16>,
+    output_weights: Vec<i16>,
+    output_bias: i32,
+}
+
+impl Network {
+    /// Create a network from its raw weights.
+    ///
+    /// The slices must have the lengths described in the weight file format.
+    pub fn new(
+        feature_weights: Vec<i16>,
+        feature_biases: Vec<i16>,
+        output_weights: Vec<i16>,
+        output_bias: i32,
+    ) -> Result<Self, NnueError> {
+        let hidden = feature_biases.len();
+        if hidden == 0 {
+            return Err(NnueError::InvalidHiddenSize(hidden));
+        }
+        if feature_weights.len() != INPUTS * hidden {
+            return Err(NnueError::FeatureWeightCount(
+                INPUTS * hidden,
+                feature_weights.len(),
+            ));
+        }
+        if output_weights.len() != 2 * hidden {
+            return Err(NnueError::OutputWeightCount(
+                2 * hidden,
+                output_weights.len(),
+            ));
+        }
+        Ok(Self {
+            hidden,
+            feature_weights,
+            feature_biases,
+            output_weights,
+            output_bias,
+        })
+    }
+
+    /// Load a network from the bytes of a weight file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, NnueError> {
+        let mut reader = Reader { bytes, offset: 0 };
+        if reader.take(4)? != MAGIC {
+            return Err(NnueError::InvalidMagic);
+        }
+        let version = reader.u32()?;
+        if version != VERSION {
+            return Err(NnueError::UnsupportedVersion(version));
+        }
+        let hidden = reader.u32()? as usize;
+        if hidden == 0 || hidden > 1 << 16 {
+            return Err(NnueError::InvalidHiddenSize(hidden));
+        }
+
+        let feature_weights = reader.i16s(INPUTS * hidden)?;
+        let feature_biases = reader.i16s(hidden)?;
+        let output_weights = reader.i16s(2 * hidden)?;
+        let output_bias = reader.u32()? as i32;
+        if reader.offset != bytes.len() {
+            return Err(NnueError::TrailingBytes);
+        }
+
+        Self::new(feature_weights, feature_biases, output_weights, output_bias)
+    }
+
+    /// Save a network in the weight file format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(16 + 2 * (INPUTS + 3) * self.hidden);
+        result.extend_from_slice(MAGIC);
+        result.extend_from_slice(&VERSION.to_le_bytes());
+        result.extend_from_slice(&(self.hidden as u32).to_le_bytes());
+        for w in self
+            .feature_weights
+            .iter()
+            .chain(&self.feature_biases)
+            .chain(&self.output_weights)
+        {
+            result.extend_from_slice(&w.to_le_bytes());
+        }
+        result.extend_from_slice(&self.output_bias.to_le_bytes());
+        result
+    }
+
+    /// Get the size of the hidden layer.
+    #[inline]
+    pub fn hidden_size(&self) -> usize {
+        self.hidden
+    }
+
+    /// Wrap a board so that it is evaluated and searched with this network.
+    pub fn with_board(&self, board: Board) -> NnueBoard<'_> {
+        NnueBoard {
+            board,
+            accumulator: Accumulator::refresh(self, &board),
+            network: self,
+        }
+    }
+
+    #[inline]
+    fn feature(&self, perspective: Color, piece: &Piece) -> &[i16] {
+        let index = feature_index(perspective, piece);
+        &self.feature_weights[index * self.hidden..(index + 1) * self.hidden]
+    }
+
+    // summed in `i64`, since a large hidden layer with large output
+    // weights overflows `i32` before the output is scaled down
+    fn output(&self, us: &[i16], them: &[i16]) -> i32 {
+        let (our_weights, their_weights) = self.output_weights.split_at(self.hidden);
+        let mut sum = 0i64;
+        for (x, w) in us.iter().zip(our_weights) {
+            sum += (crelu(*x) * *w as i32) as i64;
+        }
+        for (x, w) in them.iter().zip(their_weights) {
+            sum += (crelu(*x) * *w as i32) as i64;
+        }
+        let output = (sum + self.output_bias as i64) * SCALE as i64 / (QA * QB) as i64;
+        output.clamp(i32::MIN as i64, i32::MAX as i64) as i32
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], NnueError> {
+        let end = self.offset + len;
+        if end > self.bytes.len() {
+            return Err(NnueError::UnexpectedEnd);
+        }
+        let result = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(result)
+    }
+
+    fn u32(&mut self) -> Result<u32, NnueError> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn i16s(&mut self, count: usize) -> Result<Vec<i16>, NnueError> {
+        Ok(self
+            .take(2 * count)?
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+            .collect())
+    }
+}
+
+#[inline]
+fn crelu(x: i16) -> i32 {
+    (x as i32).clamp(0, QA)
+}
+
+#[inline]
+fn feature_index(perspective: Color, piece: &Piece) -> usize {
+    let pos = piece.get_pos();
+    let row = match perspective {
+        Color::White => pos.get_row(),
+        Color::Black => 7 - pos.get_row(),
+    };
+    let side = if piece.get_color() == perspective {
+        0
+    } else {
+        1
+    };
+    side * 384 + piece.get_kind() as usize * 64 + (row * 8 + pos.get_col()) as usize
+}
+
+/// The first layer's output for both perspectives.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Accumulator {
+    white: Vec<i16>,
+    black: Vec<i16>,
+}
+
+impl Accumulator {
+    /// Compute the accumulator of a board from scratch.
+    fn refresh(network: &Network, board: &Board) -> Self {
+        let mut result = Self {
+            white: network.feature_biases.clone(),
+            black: network.feature_biases.clone(),
+        };
+        for piece in board.pieces() {
+            result.add(network, &piece);
+        }
+        result
+    }
+
+    #[inline]
+    fn add(&mut self, network: &Network, piece: &Piece) {
+        add_assign(&mut self.white, network.feature(Color::White, piece));
+        add_assign(&mut self.black, network.feature(Color::Black, piece));
+    }
+
+    #[inline]
+    fn sub(&mut self, network: &Network, piece: &Piece) {
+        sub_assign(&mut self.white, network.feature(Color::White, piece));
+        sub_assign(&mut self.black, network.feature(Color::Black, piece));
+    }
+
+    /// Update the accumulator for a move played on `before`, which gave
+    /// `after`. Only the squares the move can change are compared.
+    fn update(&mut self, network: &Network, before: &Board, after: &Board, m: Move) {
+        let mut squares = ChangedSquares::default();
+        match m {
+            Move::Piece(from, to) | Move::Promotion(from, to, _) => {
+                squares.push(from);
+                squares.push(to);
+                // the pawn taken en passant
+                squares.push(Position::new(from.get_row(), to.get_col()));
+                // the pieces caught in an atomic explosion
+                if before.get_variant() == Variant::Atomic {
+                    for row in to.get_row() - 1..=to.get_row() + 1 {
+                        for col in to.get_col() - 1..=to.get_col() + 1 {
+                            squares.push(Position::new(row, col));
+                        }
+                    }
+                }
+            }
+            // in Chess960 the king and rook can start anywhere on the back rank
+            Move::KingSideCastle | Move::QueenSideCastle => {
+                let row = match before.get_turn_color() {
+                    Color::White => 0,
+                    Color::Black => 7,
+                };
+                for col in 0..8 {
+                    squares.push(Position::new(row, col));
+                }
+            }
+            Move::Drop(_, pos) => squares.push(pos),
+            Move::Null => {}
+        }
+
+        for pos in squares.as_slice() {
+            let (old, new) = (before.get_piece(*pos), after.get_piece(*pos));
+            if old != new {
+                if let Some(piece) = old {
+                    self.sub(network, &piece);
+                }
+                if let Some(piece) = new {
+                    self.add(network, &piece);
+                }
+            }
+        }
+    }
+}
+
+/// The distinct squares a move can change: at most the from square, the
+/// en passant square, and the 3x3 block around the target square that an
+/// atomic explosion clears.
+struct ChangedSquares {
+    squares: [Position; 11],
+    len: usize,
+}
+
+impl Default for ChangedSquares {
+    fn default() -> Self {
+        Self {
+            squares: [Position::new(0, 0); 11],
+            len: 0,
+        }
+    }
+}
+
+impl ChangedSquares {
+    #[inline]
+    fn push(&mut self, pos: Position) {
+        if pos.is_on_board() && !self.as_slice().contains(&pos) {
+            self.squares[self.len] = pos;
+            self.len += 1;
+        }
+    }
+
+    #[inline]
+    fn as_slice(&self) -> &[Position] {
+        &self.squares[..self.len]
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[inline]
+fn add_assign(acc: &mut [i16], weights: &[i16]) {
+    use core::arch::x86_64::{__m128i, _mm_add_epi16, _mm_loadu_si128, _mm_storeu_si128};
+
+    debug_assert_eq!(acc.len(), weights.len());
+    let chunks = acc.len() / 8;
+    // SSE2 is part of the x86_64 baseline, so it is always available.
+    unsafe {
+        for i in 0..chunks {
+            let a = acc.as_mut_ptr().add(i * 8) as *mut __m128i;
+            let w = weights.as_ptr().add(i * 8) as *const __m128i;
+            _mm_storeu_si128(a, _mm_add_epi16(_mm_loadu_si128(a), _mm_loadu_si128(w)));
+        }
+    }
+    add_assign_scalar(&mut acc[chunks * 8..], &weights[chunks * 8..]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[inline]
+fn sub_assign(acc: &mut [i16], weights: &[i16]) {
+    use core::arch::x86_64::{__m128i, _mm_loadu_si128, _mm_storeu_si128, _mm_sub_epi16};
+
+    debug_assert_eq!(acc.len(), weights.len());
+    let chunks = acc.len() / 8;
+    // SSE2 is part of the x86_64 baseline, so it is always available.
+    unsafe {
+        for i in 0..chunks {
+            let a = acc.as_mut_ptr().add(i * 8) as *mut __m128i;
+            let w = weights.as_ptr().add(i * 8) as *const __m128i;
+            _mm_storeu_si128(a, _mm_sub_epi16(_mm_loadu_si128(a), _mm_loadu_si128(w)));
+        }
+    }
+    sub_assign_scalar(&mut acc[chunks * 8..], &weights[chunks * 8..]);
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+#[inline]
+fn add_assign(acc: &mut [i16], weights: &[i16]) {
+    add_assign_scalar(acc, weights)
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+#[inline]
+fn sub_assign(acc: &mut [i16], weights: &[i16]) {
+    sub_assign_scalar(acc, weights)
+}
+
+#[inline]
+fn add_assign_scalar(acc: &mut [i16], weights: &[i16]) {
+    for (a, w) in acc.iter_mut().zip(weights) {
+        *a = a.wrapping_add(*w);
+    }
+}
+
+#[inline]
+fn sub_assign_scalar(acc: &mut [i16], weights: &[i16]) {
+    for (a, w) in acc.iter_mut().zip(weights) {
+        *a = a.wrapping_sub(*w);
+    }
+}
+
+/// A board that is evaluated with an NNUE network.
+///
+/// Applying a move updates the accumulators incrementally from the
+/// squares the move changed, so this can be searched with
+/// `get_best_next_move` like any other `Evaluate` type.
+#[derive(Clone, Debug)]
+pub struct NnueBoard<'a> {
+    board: Board,
+    accumulator: Accumulator,
+    network: &'a Network,
+}
+
+impl<'a> NnueBoard<'a> {
+    /// Get the board being evaluated.
+    #[inline]
+    pub fn get_board(&self) -> &Board {
+        &self.board
+    }
+}
+
+impl<'a> Evaluate for NnueBoard<'a> {
+    /// The network's output for a color, in the same units as
+    /// `Board::value_for` (a pawn is worth 10).
+    #[inline]
+    fn value_for(&self, color: Color) -> f64 {
+        let (us, them) = match color {
+            Color::White => (&self.accumulator.white, &self.accumulator.black),
+            Color::Black => (&self.accumulator.black, &self.accumulator.white),
+        };
+        self.network.output(us, them) as f64 / 10.0
+    }
+
+    #[inline]
+    fn get_current_player_color(&self) -> Color {
+        self.board.get_current_player_color()
+    }
+
+    #[inline]
+    fn get_legal_moves(&self) -> Vec<Move> {
+        self.board.get_legal_moves()
+    }
+
+    #[inline]
+    fn get_no_moves_value(&self, color: Color) -> f64 {
+        self.board.get_no_moves_value(color)
+    }
+
+    fn apply_eval_move(&self, m: Move) -> Self {
+        let board = self.board.apply_eval_move(m);
+        let mut accumulator = self.accumulator.clone();
+        accumulator.update(self.network, &self.board, &board, m);
+        Self {
+            board,
+            accumulator,
+            network: self.network,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::*;
+    use crate::util::parse_fen;
+
+    // a small deterministic network with pseudo-random weights
+    fn test_network(hidden: usize) -> Network {
+        let mut state = 0x2545_f491_u32;
+        let mut next = |range: i32| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            (state % (2 * range as u32 + 1)) as i32 - range
+        };
+        let feature_weights = (0..INPUTS * hidden).map(|_| next(40) as i16).collect();
+        let feature_biases = (0..hidden).map(|_| next(20) as i16).collect();
+        let output_weights = (0..2 * hidden).map(|_| next(60) as i16).collect();
+        Network::new(feature_weights, feature_biases, output_weights, next(500)).unwrap()
+    }
+
+    #[test]
+    fn test_weight_file_round_trip() {
+        let network = test_network(12);
+        let bytes = network.to_bytes();
+        assert_eq!(bytes.len(), 16 + 2 * (INPUTS + 3) * 12);
+        assert_eq!(Network::from_bytes(&bytes).unwrap(), network);
+
+        assert_eq!(
+            Network::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(NnueError::UnexpectedEnd)
+        );
+        assert_eq!(Network::from_bytes(b"NOPE"), Err(NnueError::InvalidMagic));
+        let mut extra = bytes.clone();
+        extra.push(0);
+        assert_eq!(Network::from_bytes(&extra), Err(NnueError::TrailingBytes));
+        let mut version = bytes.clone();
+        version[4] = 2;
+        assert_eq!(
+            Network::from_bytes(&version),
+            Err(NnueError::UnsupportedVersion(2))
+        );
+        assert_eq!(
+            Network::new(vec![0; 10], vec![0; 1], vec![0; 2], 0),
+            Err(NnueError::FeatureWeightCount(INPUTS, 10))
+        );
+    }
+
+    #[test]
+    fn test_incremental_matches_refresh() {
+        // 20 hidden neurons exercises both the vectorized and scalar paths
+        let network = test_network(20);
+        let mut board =
+            network.with_board(parse_fen("r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1").unwrap());
+        for m in [
+            Move::Piece(E5, D6),
+            Move::KingSideCastle,
+            Move::QueenSideCastle,
+            Move::Piece(G8, G7),
+            Move::Promotion(B7, A8, Piece::Knight(Color::White, A8)),
+        ] {
+            let color = board.get_current_player_color();
+            assert!(board.get_board().is_legal_move(m, color), "{}", m);
+            board = board.apply_eval_move(m);
+            assert_eq!(
+                board.accumulator,
+                Accumulator::refresh(&network, board.get_board()),
+                "{}",
+                m
+            );
+        }
+
+        // Chess960 castling, and an atomic capture exploding its neighbours
+        for (fen, variant, m) in [
+            (
+                "1r2k1r1/8/8/8/8/8/8/1R2K1R1 w GBgb - 0 1",
+                Variant::Standard,
+                Move::QueenSideCastle,
+            ),
+            (
+                "4k3/8/8/2nbr3/3Q4/8/8/4K3 w - - 0 1",
+                Variant::Atomic,
+                Move::Piece(D4, D5),
+            ),
+        ] {
+            let board = network.with_board(parse_fen(fen).unwrap().set_variant(variant));
+            assert!(board.get_board().is_legal_move(m, Color::White), "{}", m);
+            let board = board.apply_eval_move(m);
+            assert_eq!(
+                board.accumulator,
+                Accumulator::refresh(&network, board.get_board())
+            );
+        }
+    }
+
+    #[test]
+    fn test_large_output_weights() {
+        // every hidden neuron saturates and meets the largest output weight
+        let hidden = 256;
+        let network = Network::new(
+            vec![0; INPUTS * hidden],
+            vec![QA as i16; hidden],
+            vec![i16::MAX; 2 * hidden],
+            0,
+        )
+        .unwrap();
+        let board = network.with_board(Board::default());
+        let sum = 2 * hidden as i64 * QA as i64 * i16::MAX as i64;
+        let expected = sum * SCALE as i64 / (QA * QB) as i64;
+        assert_eq!(board.value_for(Color::White), expected as f64 / 10.0);
+    }
+
+    #[test]
+    fn test_symmetric_evaluation() {
+        let network = test_network(16);
+        let board = network.with_board(Board::default());
+        assert_eq!(board.value_for(Color::White), board.value_for(Color::Black));
+
+        let mut scalar = network.feature_biases.clone();
+        add_assign_scalar(
+            &mut scalar,
+            network.feature(Color::White, &Piece::Pawn(Color::White, E4)),
+        );
+        let mut simd = network.feature_biases.clone();
+        add_assign(
+            &mut simd,
+            network.feature(Color::White, &Piece::Pawn(Color::White, E4)),
+        );
+        assert_eq!(scalar, simd);
+    }
+}
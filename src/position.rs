@@ -1,8 +1,5 @@
-use super::Color;
-use alloc::{
-    string::{String, ToString},
-    vec::Vec,
-};
+use super::{Color, SquareParseError};
+use alloc::vec::Vec;
 
 pub const A1: Position = Position::new(0, 0);
 pub const A2: Position = Position::new(1, 0);
@@ -137,33 +134,22 @@ impl Position {
 
     /// Parse a position from PGN. This simply just supports positions like
     /// `e4` and `D8`.
-    pub fn pgn(s: &str) -> Result<Self, String> {
+    pub fn pgn(s: &str) -> Result<Self, SquareParseError> {
         let s = s.trim().to_lowercase();
-        let col = s.chars().next().ok_or(format!("invalid pgn `{}`", s))?;
-        let row = s
-            .chars()
-            .nth(1)
-            .ok_or(format!("invalid pgn `{}`", s))?
-            .to_string()
-            .parse::<u32>()
-            .map_err(|_| format!("invalid pgn `{}`", s))? as i32;
+        let mut chars = s.chars();
+        let (col, row) = match (chars.next(), chars.next(), chars.next()) {
+            (Some(col), Some(row), None) => (col, row),
+            _ => return Err(SquareParseError::WrongLength),
+        };
         let c = match col {
-            'a' => 0,
-            'b' => 1,
-            'c' => 2,
-            'd' => 3,
-            'e' => 4,
-            'f' => 5,
-            'g' => 6,
-            'h' => 7,
-            _ => return Err(format!("invalid column character `{}`", col)),
+            'a'..='h' => col as i32 - 'a' as i32,
+            _ => return Err(SquareParseError::InvalidFile(col)),
         };
-
-        if 1 <= row || row <= 8 {
-            Ok(Self::new(row - 1, c))
-        } else {
-            Err(format!("invalid row number `{}`", row))
-        }
+        let r = match row {
+            '1'..='8' => row as i32 - '1' as i32,
+            _ => return Err(SquareParseError::InvalidRank(row)),
+        };
+        Ok(Self::new(r, c))
     }
 
     /// Is this position a valid spot on the board?
@@ -385,19 +371,8 @@ impl Position {
             return Vec::new();
         }
 
-        let row_step;
-        let col_step;
-        if self.is_left_of(to) {
-            col_step = 1;
-        } else {
-            col_step = -1;
-        }
-
-        if self.is_below(to) {
-            row_step = 1;
-        } else {
-            row_step = -1;
-        }
+        let col_step = if self.is_left_of(to) { 1 } else { -1 };
+        let row_step = if self.is_below(to) { 1 } else { -1 };
 
         let mut acc = *self;
         let mut result = Vec::new();
@@ -0,0 +1,1505 @@
+//! A reader for Syzygy endgame tablebases.
+//!
+//! Syzygy tablebases come as two files for every set of pieces, named after
+//! the pieces such as `KQvK.rtbw` and `KQvK.rtbz`. The WDL file (`.rtbw`)
+//! has the outcome of every position, and the DTZ file (`.rtbz`) has the
+//! distance to zeroing of the won and lost positions, for one side to move.
+//! The values are compressed in small blocks, and a position is found by an
+//! index made from the squares of its pieces, after mirroring the board so
+//! that mirrored positions are only stored once.
+//!
+//! `Syzygy` finds the files in the directories it is given and reads each
+//! one the first time a position needs it. Probing follows the reference
+//! reader that comes with the generator: the tables leave out positions
+//! where a capture is the best move, so captures are always searched before
+//! a table is looked at.
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use core::fmt;
+use std::{
+    ffi::OsStr,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use crate::tablebase::can_probe;
+use crate::{Board, Color, Evaluate, Move, PieceKind, Tablebase, Wdl};
+
+// the most pieces, kings included, of any Syzygy table
+const MAX_PIECES: usize = 7;
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xe8, 0x23, 0x5d];
+const DTZ_MAGIC: [u8; 4] = [0xd7, 0x66, 0x0c, 0xa5];
+
+// the flags of a table, after its magic
+const SPLIT: u8 = 1;
+const HAS_PAWNS: u8 = 2;
+
+// the flags of the values for one side to move and one file of the
+// leading pawn
+const STM: u8 = 1;
+const MAPPED: u8 = 2;
+const WIN_PLIES: u8 = 4;
+const LOSS_PLIES: u8 = 8;
+const WIDE: u8 = 16;
+const SINGLE_VALUE: u8 = 128;
+
+// the DTZ map of each outcome, from a loss to a win
+const WDL_MAP: [usize; 5] = [1, 3, 0, 2, 0];
+
+// the ways to place three unique pieces, and two kings, once mirrored
+// positions are left out
+const UNIQUE_PIECES_SIZE: u64 = 31332;
+const KINGS_SIZE: u64 = 462;
+
+#[inline]
+fn rank_of(square: usize) -> usize {
+    square >> 3
+}
+
+#[inline]
+fn file_of(square: usize) -> usize {
+    square & 7
+}
+
+// how far above the a1-h8 diagonal a square is, or below when negative
+#[inline]
+fn off_diagonal(square: usize) -> i32 {
+    rank_of(square) as i32 - file_of(square) as i32
+}
+
+#[inline]
+fn flip_diagonal(square: usize) -> usize {
+    ((square >> 3) | (square << 3)) & 63
+}
+
+#[inline]
+fn distance(a: usize, b: usize) -> usize {
+    rank_of(a)
+        .abs_diff(rank_of(b))
+        .max(file_of(a).abs_diff(file_of(b)))
+}
+
+// the tables that turn the squares of the pieces into an index
+struct Indices {
+    binomial: [[u64; 64]; MAX_PIECES + 1],
+    // the squares a2-h7, numbered from the second rank up and from the
+    // edges in, so the leading pawn is the one with the highest number
+    map_pawns: [usize; 64],
+    lead_pawn_idx: [[u64; 64]; MAX_PIECES + 1],
+    lead_pawns_size: [[u64; 4]; MAX_PIECES + 1],
+    // the triangle a1-d1-d4, with the diagonal numbered last
+    map_a1d1d4: [usize; 64],
+    // the squares below the a1-h8 diagonal
+    map_b1h1h7: [usize; 64],
+    map_kk: [[u64; 64]; 10],
+}
+
+fn indices() -> &'static Indices {
+    static INDICES: OnceLock<Indices> = OnceLock::new();
+    INDICES.get_or_init(Indices::new)
+}
+
+impl Indices {
+    // the tables are filled square by square, the way the generator does
+    #[allow(clippy::needless_range_loop)]
+    fn new() -> Self {
+        let mut binomial = [[0; 64]; MAX_PIECES + 1];
+        binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..=MAX_PIECES.min(n) {
+                binomial[k][n] = if k > 0 { binomial[k - 1][n - 1] } else { 0 }
+                    + if k < n { binomial[k][n - 1] } else { 0 };
+            }
+        }
+
+        let mut map_pawns = [0; 64];
+        let mut lead_pawn_idx = [[0; 64]; MAX_PIECES + 1];
+        let mut lead_pawns_size = [[0; 4]; MAX_PIECES + 1];
+        let mut available = 48;
+        for count in 1..MAX_PIECES {
+            for file in 0..4 {
+                let mut idx = 0;
+                for rank in 1..7 {
+                    let square = rank * 8 + file;
+                    if count == 1 {
+                        map_pawns[square] = available - 1;
+                        map_pawns[square ^ 7] = available - 2;
+                        available -= 2;
+                    }
+                    lead_pawn_idx[count][square] = idx;
+                    idx += binomial[count - 1][map_pawns[square]];
+                }
+                lead_pawns_size[count][file] = idx;
+            }
+        }
+
+        let mut map_b1h1h7 = [0; 64];
+        let mut code = 0;
+        for square in 0..64 {
+            if off_diagonal(square) < 0 {
+                map_b1h1h7[square] = code;
+                code += 1;
+            }
+        }
+
+        let mut map_a1d1d4 = [0; 64];
+        let mut diagonal = vec![];
+        let mut code = 0;
+        for square in 0..28 {
+            if off_diagonal(square) < 0 && file_of(square) <= 3 {
+                map_a1d1d4[square] = code;
+                code += 1;
+            } else if off_diagonal(square) == 0 && file_of(square) <= 3 {
+                diagonal.push(square);
+            }
+        }
+        for square in diagonal {
+            map_a1d1d4[square] = code;
+            code += 1;
+        }
+
+        // kings next to each other are left out, and so are kings that are
+        // the same as another pair mirrored on the diagonal
+        let mut map_kk = [[0; 64]; 10];
+        let mut both_on_diagonal = vec![];
+        let mut code = 0;
+        for idx in 0..10 {
+            for first in 0..28 {
+                // b1 is the only square of the triangle numbered 0
+                if map_a1d1d4[first] != idx || (idx == 0 && first != 1) {
+                    continue;
+                }
+                for second in 0..64 {
+                    if distance(first, second) <= 1
+                        || (off_diagonal(first) == 0 && off_diagonal(second) > 0)
+                    {
+                        continue;
+                    } else if off_diagonal(first) == 0 && off_diagonal(second) == 0 {
+                        both_on_diagonal.push((idx, second));
+                    } else {
+                        map_kk[idx][second] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (idx, second) in both_on_diagonal {
+            map_kk[idx][second] = code;
+            code += 1;
+        }
+
+        Self {
+            binomial,
+            map_pawns,
+            lead_pawn_idx,
+            lead_pawns_size,
+            map_a1d1d4,
+            map_b1h1h7,
+            map_kk,
+        }
+    }
+}
+
+#[inline]
+fn read<const N: usize>(bytes: &[u8], at: usize) -> Option<[u8; N]> {
+    bytes.get(at..at.checked_add(N)?)?.try_into().ok()
+}
+
+#[inline]
+fn read_u8(bytes: &[u8], at: usize) -> Option<u8> {
+    bytes.get(at).copied()
+}
+
+#[inline]
+fn read_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    read(bytes, at).map(u16::from_le_bytes)
+}
+
+#[inline]
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    read(bytes, at).map(u32::from_le_bytes)
+}
+
+// the two symbols a symbol stands for, or a value and 0xfff for symbols
+// that stand for a single value
+fn read_pair(bytes: &[u8], btree: usize, symbol: usize) -> Option<(usize, usize)> {
+    let [a, b, c] = read::<3>(bytes, btree.checked_add(3 * symbol)?)?;
+    Some((
+        a as usize | (b as usize & 0xf) << 8,
+        (b as usize) >> 4 | (c as usize) << 4,
+    ))
+}
+
+// get how many values each symbol stands for, less one
+fn read_symbol_lengths(bytes: &[u8], btree: usize, symbols: usize) -> Option<Vec<u8>> {
+    fn set_length(
+        bytes: &[u8],
+        btree: usize,
+        symbol: usize,
+        lengths: &mut [u8],
+        visited: &mut [bool],
+    ) -> Option<u8> {
+        visited[symbol] = true;
+        let (left, right) = read_pair(bytes, btree, symbol)?;
+        if right == 0xfff {
+            return Some(0);
+        }
+        for child in [left, right] {
+            if !*visited.get(child)? {
+                lengths[child] = set_length(bytes, btree, child, lengths, visited)?;
+            }
+        }
+        u8::try_from(lengths[left] as usize + lengths[right] as usize + 1).ok()
+    }
+
+    let mut lengths = vec![0; symbols];
+    let mut visited = vec![false; symbols];
+    for symbol in 0..symbols {
+        if !visited[symbol] {
+            lengths[symbol] = set_length(bytes, btree, symbol, &mut lengths, &mut visited)?;
+        }
+    }
+    Some(lengths)
+}
+
+// the code of a piece in a table: 1 for a white pawn up to 6 for a white
+// king, and 8 more for black pieces
+fn piece_code(kind: PieceKind, side: usize) -> u8 {
+    let code = match kind {
+        PieceKind::Pawn => 1,
+        PieceKind::Knight => 2,
+        PieceKind::Bishop => 3,
+        PieceKind::Rook => 4,
+        PieceKind::Queen => 5,
+        PieceKind::King => 6,
+    };
+    code + 8 * side as u8
+}
+
+// the pieces of a table, counted for each side in the order of
+// `PieceKind::ALL`, with the side named first in the table as white
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Material([[u8; 6]; 2]);
+
+impl Material {
+    // read the pieces from a table name such as `KRPvKR`
+    fn parse(name: &str) -> Option<Self> {
+        let (white, black) = name.split_once('v')?;
+        if white.len() + black.len() > MAX_PIECES {
+            return None;
+        }
+
+        let mut counts = [[0; 6]; 2];
+        for (side, letters) in [white, black].into_iter().enumerate() {
+            for letter in letters.chars() {
+                if !letter.is_ascii_uppercase() {
+                    return None;
+                }
+                counts[side][PieceKind::from_letter(letter)?.index()] += 1;
+            }
+            if counts[side][PieceKind::King.index()] != 1 {
+                return None;
+            }
+        }
+        Some(Self(counts))
+    }
+
+    fn of(board: &Board) -> Self {
+        let mut counts = [[0; 6]; 2];
+        for piece in board.pieces() {
+            counts[piece.get_color() as usize][piece.get_kind().index()] += 1;
+        }
+        Self(counts)
+    }
+
+    fn name(&self) -> String {
+        let mut result = String::new();
+        for (side, counts) in self.0.iter().enumerate() {
+            if side == 1 {
+                result.push('v');
+            }
+            for kind in PieceKind::ALL {
+                for _ in 0..counts[kind.index()] {
+                    result.push(kind.get_letter());
+                }
+            }
+        }
+        result
+    }
+
+    #[inline]
+    fn flip(&self) -> Self {
+        Self([self.0[1], self.0[0]])
+    }
+
+    #[inline]
+    fn count(&self) -> usize {
+        self.0.iter().flatten().map(|&count| count as usize).sum()
+    }
+
+    #[inline]
+    fn pawns(&self, side: usize) -> usize {
+        self.0[side][PieceKind::Pawn.index()] as usize
+    }
+
+    #[inline]
+    fn is_symmetric(&self) -> bool {
+        self.0[0] == self.0[1]
+    }
+
+    fn has_unique_pieces(&self) -> bool {
+        self.0.iter().any(|counts| {
+            PieceKind::ALL
+                .into_iter()
+                .any(|kind| kind != PieceKind::King && counts[kind.index()] == 1)
+        })
+    }
+
+    // the codes of every piece, in order
+    fn codes(&self) -> Vec<u8> {
+        let mut result = vec![];
+        for (side, counts) in self.0.iter().enumerate() {
+            for kind in PieceKind::ALL {
+                for _ in 0..counts[kind.index()] {
+                    result.push(piece_code(kind, side));
+                }
+            }
+        }
+        result.sort_unstable();
+        result
+    }
+}
+
+// a position as a table has it, with the side named first in the table
+// as white, and squares numbered from 0 for a1 to 63 for h8
+#[derive(Clone, Copy, Debug, Default)]
+struct TablePosition {
+    pieces: [u8; MAX_PIECES],
+    squares: [usize; MAX_PIECES],
+    len: usize,
+    stm: usize,
+}
+
+impl TablePosition {
+    // turn the board around when the table names black's pieces first
+    fn new(board: &Board, flip: bool) -> Self {
+        let mut result = Self {
+            stm: (board.get_turn_color() == Color::Black) as usize ^ flip as usize,
+            ..Self::default()
+        };
+        for piece in board.pieces() {
+            let pos = piece.get_pos();
+            let side = (piece.get_color() == Color::Black) as usize ^ flip as usize;
+            let square = (pos.get_row() * 8 + pos.get_col()) as usize;
+            result.push(
+                piece_code(piece.get_kind(), side),
+                if flip { square ^ 56 } else { square },
+            );
+        }
+        result
+    }
+
+    #[inline]
+    fn push(&mut self, piece: u8, square: usize) {
+        self.pieces[self.len] = piece;
+        self.squares[self.len] = square;
+        self.len += 1;
+    }
+}
+
+// the values of a table for one side to move and one file of the leading
+// pawn, and where their compressed data is
+#[derive(Clone, Debug, Default)]
+struct PairsData {
+    flags: u8,
+    pieces: [u8; MAX_PIECES],
+    group_len: [usize; MAX_PIECES + 1],
+    group_idx: [u64; MAX_PIECES + 1],
+    size: u64,
+    block_size: usize,
+    span: u64,
+    num_blocks: usize,
+    min_symbol_length: u8,
+    lowest_symbol: usize,
+    base64: Vec<u64>,
+    symbol_lengths: Vec<u8>,
+    btree: usize,
+    sparse_index: usize,
+    sparse_index_size: usize,
+    block_lengths: usize,
+    block_lengths_size: usize,
+    data: usize,
+    map_idx: [usize; 4],
+}
+
+// where a position is in a table
+enum Index {
+    Found {
+        file: usize,
+        side: usize,
+        index: u64,
+    },
+    // the table is a DTZ table for the other side to move
+    OtherSide,
+}
+
+// the value of a position in a table
+enum Probe {
+    Found(i32),
+    OtherSide,
+}
+
+// a WDL or DTZ table read into memory
+struct Table {
+    bytes: Vec<u8>,
+    dtz: bool,
+    piece_count: usize,
+    symmetric: bool,
+    has_pawns: bool,
+    unique_pieces: bool,
+    // the pawns of the side with the leading pawns, then of the other side
+    pawns: [usize; 2],
+    sides: usize,
+    files: usize,
+    pairs: [[PairsData; 2]; 4],
+    map: usize,
+}
+
+impl Table {
+    // a table for the given pieces, with nothing read yet
+    fn new(bytes: Vec<u8>, dtz: bool, material: Material) -> Self {
+        let has_pawns = material.pawns(0) + material.pawns(1) > 0;
+        let symmetric = material.is_symmetric();
+        // the side with fewer pawns leads, which compresses better
+        let white_leads = material.pawns(1) == 0
+            || (material.pawns(0) > 0 && material.pawns(1) >= material.pawns(0));
+        Self {
+            bytes,
+            dtz,
+            piece_count: material.count(),
+            symmetric,
+            has_pawns,
+            unique_pieces: material.has_unique_pieces(),
+            pawns: if white_leads {
+                [material.pawns(0), material.pawns(1)]
+            } else {
+                [material.pawns(1), material.pawns(0)]
+            },
+            sides: if !dtz && !symmetric { 2 } else { 1 },
+            files: if has_pawns { 4 } else { 1 },
+            pairs: Default::default(),
+            map: 0,
+        }
+    }
+
+    // read a table for the given pieces, which is `None` when it is not a
+    // valid table for them
+    fn parse(bytes: Vec<u8>, dtz: bool, material: Material) -> Option<Self> {
+        let mut table = Self::new(bytes, dtz, material);
+        let magic = if dtz { DTZ_MAGIC } else { WDL_MAGIC };
+        let flags = read_u8(&table.bytes, 4)?;
+        if table.bytes[..4] != magic
+            || (flags & HAS_PAWNS != 0) != table.has_pawns
+            || (flags & SPLIT != 0) == table.symmetric
+        {
+            return None;
+        }
+
+        let codes = material.codes();
+        let two_orders = table.has_pawns && table.pawns[1] > 0;
+        let mut p = 5;
+        for file in 0..table.files {
+            let first = read_u8(&table.bytes, p)?;
+            let second = if two_orders {
+                read_u8(&table.bytes, p + 1)?
+            } else {
+                0xff
+            };
+            let orders = [[first & 0xf, second & 0xf], [first >> 4, second >> 4]];
+            p += 1 + two_orders as usize;
+
+            for k in 0..table.piece_count {
+                let pieces = read_u8(&table.bytes, p + k)?;
+                for side in 0..table.sides {
+                    table.pairs[file][side].pieces[k] =
+                        if side == 1 { pieces >> 4 } else { pieces & 0xf };
+                }
+            }
+            p += table.piece_count;
+
+            for (side, order) in orders.into_iter().enumerate().take(table.sides) {
+                let d = &table.pairs[file][side];
+                let mut pieces = d.pieces[..table.piece_count].to_vec();
+                pieces.sort_unstable();
+                if pieces != codes {
+                    return None;
+                }
+                table.set_groups(file, side, order);
+
+                // the leading pawns come first, all of them
+                let d = &table.pairs[file][side];
+                if table.has_pawns && (d.pieces[0] & 7 != 1 || d.group_len[0] != table.pawns[0]) {
+                    return None;
+                }
+            }
+        }
+        p += p & 1;
+
+        for file in 0..table.files {
+            for side in 0..table.sides {
+                p = table.set_sizes(file, side, p)?;
+            }
+        }
+        if dtz {
+            table.map = p;
+            p = table.set_dtz_map(p)?;
+        }
+        for file in 0..table.files {
+            for side in 0..table.sides {
+                let d = &mut table.pairs[file][side];
+                d.sparse_index = p;
+                p = p.checked_add(d.sparse_index_size.checked_mul(6)?)?;
+            }
+        }
+        for file in 0..table.files {
+            for side in 0..table.sides {
+                let d = &mut table.pairs[file][side];
+                d.block_lengths = p;
+                p = p.checked_add(d.block_lengths_size.checked_mul(2)?)?;
+            }
+        }
+        for file in 0..table.files {
+            for side in 0..table.sides {
+                let d = &mut table.pairs[file][side];
+                p = p.checked_add(63)? & !63;
+                d.data = p;
+                p = p.checked_add(d.num_blocks.checked_mul(d.block_size)?)?;
+            }
+        }
+
+        if p > table.bytes.len() {
+            return None;
+        }
+        Some(table)
+    }
+
+    // split the pieces into groups that are indexed together, which are
+    // the leading pieces and then runs of the same piece, and work out
+    // what each group multiplies the index by
+    fn set_groups(&mut self, file: usize, side: usize, order: [u8; 2]) {
+        let indices = indices();
+        let two_orders = self.has_pawns && self.pawns[1] > 0;
+        let d = &mut self.pairs[file][side];
+
+        let mut n = 0;
+        let mut first_len: i32 = if self.has_pawns {
+            0
+        } else if self.unique_pieces {
+            3
+        } else {
+            2
+        };
+        d.group_len[0] = 1;
+        for i in 1..self.piece_count {
+            first_len -= 1;
+            if first_len > 0 || d.pieces[i] == d.pieces[i - 1] {
+                d.group_len[n] += 1;
+            } else {
+                n += 1;
+                d.group_len[n] = 1;
+            }
+        }
+        n += 1;
+        d.group_len[n] = 0;
+
+        let mut next = if two_orders { 2 } else { 1 };
+        let mut free = 64 - d.group_len[0] - if two_orders { d.group_len[1] } else { 0 };
+        let mut idx = 1;
+        let mut k = 0;
+        while next < n || k == order[0] || k == order[1] {
+            if k == order[0] {
+                d.group_idx[0] = idx;
+                idx *= if self.has_pawns {
+                    indices.lead_pawns_size[d.group_len[0]][file]
+                } else if self.unique_pieces {
+                    UNIQUE_PIECES_SIZE
+                } else {
+                    KINGS_SIZE
+                };
+            } else if k == order[1] {
+                d.group_idx[1] = idx;
+                idx *= indices.binomial[d.group_len[1]][48 - d.group_len[0]];
+            } else {
+                d.group_idx[next] = idx;
+                idx *= indices.binomial[d.group_len[next]][free];
+                free -= d.group_len[next];
+                next += 1;
+            }
+            k += 1;
+        }
+        d.group_idx[n] = idx;
+        d.size = idx;
+    }
+
+    // read how the values of a side and file are compressed
+    fn set_sizes(&mut self, file: usize, side: usize, mut p: usize) -> Option<usize> {
+        let bytes = &self.bytes;
+        let d = &mut self.pairs[file][side];
+        d.flags = read_u8(bytes, p)?;
+        if d.flags & SINGLE_VALUE != 0 {
+            d.min_symbol_length = read_u8(bytes, p + 1)?;
+            return Some(p + 2);
+        }
+
+        let block_log = read_u8(bytes, p + 1)?;
+        let span_log = read_u8(bytes, p + 2)?;
+        if !(3..32).contains(&block_log) || span_log >= 32 {
+            return None;
+        }
+        d.block_size = 1 << block_log;
+        d.span = 1 << span_log;
+        d.sparse_index_size = d.size.div_ceil(d.span) as usize;
+        let padding = read_u8(bytes, p + 3)? as usize;
+        d.num_blocks = read_u32(bytes, p + 4)? as usize;
+        d.block_lengths_size = d.num_blocks + padding;
+
+        let max_symbol_length = read_u8(bytes, p + 8)?;
+        d.min_symbol_length = read_u8(bytes, p + 9)?;
+        if d.min_symbol_length == 0
+            || max_symbol_length < d.min_symbol_length
+            || max_symbol_length > 64
+        {
+            return None;
+        }
+        p += 10;
+
+        // symbols of the same length are numbered one after the other, and
+        // longer ones lower, so the first symbol of each length is all that
+        // is needed to decode them: `base64` has the lowest code of each
+        // length, with the code in the highest bits
+        d.lowest_symbol = p;
+        let lengths = (max_symbol_length - d.min_symbol_length) as usize + 1;
+        d.base64 = vec![0; lengths];
+        for i in (0..lengths - 1).rev() {
+            let lowest = read_u16(bytes, p + 2 * i)? as u64;
+            let next_lowest = read_u16(bytes, p + 2 * i + 2)? as u64;
+            d.base64[i] = d.base64[i + 1]
+                .wrapping_add(lowest)
+                .wrapping_sub(next_lowest)
+                / 2;
+        }
+        for (i, base) in d.base64.iter_mut().enumerate() {
+            *base <<= 64 - i - d.min_symbol_length as usize;
+        }
+        p += 2 * lengths;
+
+        let symbols = read_u16(bytes, p)? as usize;
+        p += 2;
+        d.btree = p;
+        d.symbol_lengths = read_symbol_lengths(bytes, d.btree, symbols)?;
+        Some(p + 3 * symbols + (symbols & 1))
+    }
+
+    // read where the maps from stored values to distances start
+    fn set_dtz_map(&mut self, mut p: usize) -> Option<usize> {
+        for file in 0..self.files {
+            let d = &mut self.pairs[file][0];
+            if d.flags & MAPPED == 0 {
+                continue;
+            }
+            if d.flags & WIDE != 0 {
+                p += p & 1;
+                for i in 0..4 {
+                    d.map_idx[i] = (p - self.map) / 2 + 1;
+                    p += 2 + 2 * read_u16(&self.bytes, p)? as usize;
+                }
+            } else {
+                for i in 0..4 {
+                    d.map_idx[i] = p - self.map + 1;
+                    p += 1 + read_u8(&self.bytes, p)? as usize;
+                }
+            }
+        }
+        Some(p + (p & 1))
+    }
+
+    // find a position in the table
+    fn index(&self, pos: &TablePosition) -> Option<Index> {
+        let indices = indices();
+        let size = pos.len;
+        if size != self.piece_count {
+            return None;
+        }
+
+        let mut squares = [0; MAX_PIECES];
+        let mut pieces = [0; MAX_PIECES];
+        let lead_pawn = self.has_pawns.then_some(self.pairs[0][0].pieces[0]);
+        let mut lead = 0;
+        let mut file = 0;
+        if let Some(lead_pawn) = lead_pawn {
+            for i in 0..size {
+                if pos.pieces[i] == lead_pawn {
+                    squares[lead] = pos.squares[i];
+                    lead += 1;
+                }
+            }
+            let first = (0..lead).max_by_key(|&i| indices.map_pawns[squares[i]])?;
+            squares.swap(0, first);
+            file = file_of(squares[0]).min(7 - file_of(squares[0]));
+        }
+
+        let side = if self.sides == 2 { pos.stm } else { 0 };
+        let d = &self.pairs[file][side];
+        if self.dtz && (d.flags & STM) as usize != pos.stm && (self.has_pawns || !self.symmetric) {
+            return Some(Index::OtherSide);
+        }
+
+        let mut len = lead;
+        for i in 0..size {
+            if Some(pos.pieces[i]) != lead_pawn {
+                squares[len] = pos.squares[i];
+                pieces[len] = pos.pieces[i];
+                len += 1;
+            }
+        }
+        // put the pieces in the order of the table
+        for i in lead..size - 1 {
+            if let Some(j) = (i + 1..size).find(|&j| d.pieces[i] == pieces[j]) {
+                pieces.swap(i, j);
+                squares.swap(i, j);
+            }
+        }
+
+        // mirror the board so the first piece is on the a-d files
+        if file_of(squares[0]) > 3 {
+            for square in &mut squares[..size] {
+                *square ^= 7;
+            }
+        }
+
+        let mut idx;
+        if self.has_pawns {
+            idx = indices.lead_pawn_idx[lead][squares[0]];
+            squares[1..lead].sort_by_key(|&square| indices.map_pawns[square]);
+            for (i, &square) in squares[..lead].iter().enumerate().skip(1) {
+                idx += indices.binomial[i][indices.map_pawns[square]];
+            }
+        } else {
+            // without pawns, the first piece also goes on ranks 1-4, and
+            // the first piece of the leading group off the diagonal goes
+            // below it
+            if rank_of(squares[0]) > 3 {
+                for square in &mut squares[..size] {
+                    *square ^= 56;
+                }
+            }
+            for i in 0..d.group_len[0] {
+                if off_diagonal(squares[i]) == 0 {
+                    continue;
+                }
+                if off_diagonal(squares[i]) > 0 {
+                    for square in &mut squares[i..size] {
+                        *square = flip_diagonal(*square);
+                    }
+                }
+                break;
+            }
+
+            idx = if self.unique_pieces {
+                let [s0, s1, s2] = [0, 1, 2].map(|i| squares[i] as u64);
+                let [r0, r1, r2] = [0, 1, 2].map(|i| rank_of(squares[i]) as u64);
+                let adjust1 = (s1 > s0) as u64;
+                let adjust2 = (s2 > s0) as u64 + (s2 > s1) as u64;
+                if off_diagonal(squares[0]) != 0 {
+                    (indices.map_a1d1d4[squares[0]] as u64 * 63 + s1 - adjust1) * 62 + s2 - adjust2
+                } else if off_diagonal(squares[1]) != 0 {
+                    (6 * 63 + r0 * 28 + indices.map_b1h1h7[squares[1]] as u64) * 62 + s2 - adjust2
+                } else if off_diagonal(squares[2]) != 0 {
+                    6 * 63 * 62
+                        + 4 * 28 * 62
+                        + r0 * 7 * 28
+                        + (r1 - adjust1) * 28
+                        + indices.map_b1h1h7[squares[2]] as u64
+                } else {
+                    6 * 63 * 62
+                        + 4 * 28 * 62
+                        + 4 * 7 * 28
+                        + r0 * 7 * 6
+                        + (r1 - adjust1) * 6
+                        + (r2 - adjust2)
+                }
+            } else {
+                indices.map_kk[indices.map_a1d1d4[squares[0]]][squares[1]]
+            };
+        }
+
+        // the other groups are each a combination of squares, skipping the
+        // squares of the groups before them
+        idx *= d.group_idx[0];
+        let mut start = d.group_len[0];
+        let mut remaining_pawns = self.has_pawns && self.pawns[1] > 0;
+        let mut next = 1;
+        while d.group_len[next] != 0 {
+            let end = start + d.group_len[next];
+            squares[start..end].sort_unstable();
+            let mut n = 0;
+            for (i, &square) in squares[start..end].iter().enumerate() {
+                let adjust = squares[..start].iter().filter(|&&s| square > s).count();
+                let square = (square - adjust).checked_sub(8 * remaining_pawns as usize)?;
+                n += indices.binomial[i + 1][square];
+            }
+            remaining_pawns = false;
+            idx += n * d.group_idx[next];
+            start = end;
+            next += 1;
+        }
+
+        Some(Index::Found {
+            file,
+            side,
+            index: idx,
+        })
+    }
+
+    // get the value at an index
+    fn decompress(&self, d: &PairsData, idx: u64) -> Option<u16> {
+        if d.flags & SINGLE_VALUE != 0 {
+            return Some(d.min_symbol_length as u16);
+        }
+        let bytes = &self.bytes;
+
+        // the sparse index has the block and offset of the value in the
+        // middle of every span, and the block lengths lead from there to
+        // the right value
+        let k = (idx / d.span) as usize;
+        if k >= d.sparse_index_size {
+            return None;
+        }
+        let mut block = read_u32(bytes, d.sparse_index + 6 * k)? as usize;
+        let mut offset = read_u16(bytes, d.sparse_index + 6 * k + 4)? as i64
+            + (idx % d.span) as i64
+            - (d.span / 2) as i64;
+        let block_length = |block: usize| -> Option<i64> {
+            if block < d.block_lengths_size {
+                read_u16(bytes, d.block_lengths + 2 * block).map(i64::from)
+            } else {
+                None
+            }
+        };
+        while offset < 0 {
+            block = block.checked_sub(1)?;
+            offset += block_length(block)? + 1;
+        }
+        while offset > block_length(block)? {
+            offset -= block_length(block)? + 1;
+            block += 1;
+        }
+        if block >= d.num_blocks {
+            return None;
+        }
+
+        // read the block one symbol at a time until the symbol that stands
+        // for the value
+        let min_length = d.min_symbol_length as usize;
+        let mut p = d.data + block * d.block_size;
+        let mut buffer = u64::from_be_bytes(read(bytes, p)?);
+        let mut buffer_size: i32 = 64;
+        p += 8;
+        let mut symbol;
+        loop {
+            let mut len = 0;
+            while buffer < *d.base64.get(len)? {
+                len += 1;
+            }
+            symbol = (((buffer - d.base64[len]) >> (64 - len - min_length)) as u16)
+                .wrapping_add(read_u16(bytes, d.lowest_symbol + 2 * len)?)
+                as usize;
+            let length = *d.symbol_lengths.get(symbol)? as i64;
+            if offset < length + 1 {
+                break;
+            }
+            offset -= length + 1;
+            len += min_length;
+            buffer = buffer.checked_shl(len as u32)?;
+            buffer_size -= len as i32;
+            if buffer_size <= 32 {
+                buffer_size += 32;
+                let word = read(bytes, p).map_or(0, u32::from_be_bytes) as u64;
+                buffer |= word.checked_shl((64 - buffer_size) as u32).unwrap_or(0);
+                p += 4;
+            }
+        }
+
+        // then follow the pairs the symbol stands for down to the value
+        while d.symbol_lengths[symbol] != 0 {
+            let (left, right) = read_pair(bytes, d.btree, symbol)?;
+            let left_length = *d.symbol_lengths.get(left)? as i64;
+            if offset < left_length + 1 {
+                symbol = left;
+            } else {
+                offset -= left_length + 1;
+                symbol = right;
+            }
+            d.symbol_lengths.get(symbol)?;
+        }
+        read_pair(bytes, d.btree, symbol).map(|(value, _)| value as u16)
+    }
+
+    // turn a value of a DTZ table into plies, for a position with the
+    // given outcome
+    fn map_score(&self, file: usize, value: u16, wdl: i32) -> Option<i32> {
+        let d = &self.pairs[file][0];
+        let mut value = value as i32;
+        if d.flags & MAPPED != 0 {
+            let at = d.map_idx[WDL_MAP[(wdl + 2) as usize]] + value as usize;
+            value = if d.flags & WIDE != 0 {
+                read_u16(&self.bytes, self.map + 2 * at)? as i32
+            } else {
+                read_u8(&self.bytes, self.map + at)? as i32
+            };
+        }
+        if (wdl == 2 && d.flags & WIN_PLIES == 0)
+            || (wdl == -2 && d.flags & LOSS_PLIES == 0)
+            || wdl == 1
+            || wdl == -1
+        {
+            value *= 2;
+        }
+        Some(value + 1)
+    }
+
+    // get the value of a position: its outcome from -2 to 2 in a WDL
+    // table, or its distance to zeroing in a DTZ table, which needs the
+    // outcome
+    fn probe(&self, pos: &TablePosition, wdl: i32) -> Option<Probe> {
+        let (file, side, index) = match self.index(pos)? {
+            Index::Found { file, side, index } => (file, side, index),
+            Index::OtherSide => return Some(Probe::OtherSide),
+        };
+        let d = &self.pairs[file][side];
+        if index >= d.size {
+            return None;
+        }
+        let value = self.decompress(d, index)?;
+        Some(Probe::Found(if self.dtz {
+            self.map_score(file, value, wdl)?
+        } else {
+            value as i32 - 2
+        }))
+    }
+}
+
+// a table file, read the first time a position needs it
+#[derive(Default)]
+struct TableFile {
+    path: Option<PathBuf>,
+    table: OnceLock<Option<Table>>,
+}
+
+impl TableFile {
+    fn get(&self, dtz: bool, material: Material) -> Option<&Table> {
+        self.table
+            .get_or_init(|| {
+                let bytes = fs::read(self.path.as_ref()?).ok()?;
+                Table::parse(bytes, dtz, material)
+            })
+            .as_ref()
+    }
+}
+
+struct Entry {
+    material: Material,
+    wdl: TableFile,
+    dtz: TableFile,
+}
+
+/// Syzygy tablebases, read from directories of `.rtbw` and `.rtbz` files.
+///
+/// Tables are found by their file names when a directory is added, and read
+/// into memory the first time a position needs them. A table that cannot be
+/// read, or is not a valid Syzygy table, is the same as a missing one.
+#[derive(Default)]
+pub struct Syzygy {
+    tables: BTreeMap<String, Entry>,
+    max_pieces: usize,
+}
+
+impl fmt::Debug for Syzygy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Syzygy")
+            .field("tables", &self.tables.keys().collect::<Vec<_>>())
+            .field("max_pieces", &self.max_pieces)
+            .finish()
+    }
+}
+
+impl Syzygy {
+    /// Create tablebases without any tables.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create tablebases from a list of directories, separated like the
+    /// `PATH` environment variable: by `:`, or by `;` on Windows. This is
+    /// the format of the `SyzygyPath` option of UCI engines.
+    pub fn from_paths(paths: &str) -> io::Result<Self> {
+        let mut result = Self::new();
+        for path in std::env::split_paths(paths) {
+            if !path.as_os_str().is_empty() {
+                result.add_directory(path)?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Add the tables in a directory, and get the number of table files
+    /// found in it. A table already found in another directory is kept.
+    pub fn add_directory(&mut self, path: impl AsRef<Path>) -> io::Result<usize> {
+        let mut count = 0;
+        for entry in fs::read_dir(path)? {
+            let path = entry?.path();
+            let dtz = match path.extension().and_then(OsStr::to_str) {
+                Some("rtbw") => false,
+                Some("rtbz") => true,
+                _ => continue,
+            };
+            let material = match path
+                .file_stem()
+                .and_then(OsStr::to_str)
+                .and_then(Material::parse)
+            {
+                Some(material) => material,
+                None => continue,
+            };
+
+            let entry = self.tables.entry(material.name()).or_insert(Entry {
+                material,
+                wdl: TableFile::default(),
+                dtz: TableFile::default(),
+            });
+            let file = if dtz { &mut entry.dtz } else { &mut entry.wdl };
+            if file.path.is_none() {
+                file.path = Some(path);
+                count += 1;
+                if !dtz {
+                    self.max_pieces = self.max_pieces.max(material.count());
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    // find the table of a board, and the board as the table has it
+    fn find(&self, board: &Board, dtz: bool) -> Option<(&Table, TablePosition)> {
+        let material = Material::of(board);
+        let (entry, flip) = match self.tables.get(&material.name()) {
+            // tables with the same pieces on both sides only have white to
+            // move
+            Some(entry) => (
+                entry,
+                material.is_symmetric() && board.get_turn_color() == Color::Black,
+            ),
+            None => (self.tables.get(&material.flip().name())?, true),
+        };
+        let file = if dtz { &entry.dtz } else { &entry.wdl };
+        Some((
+            file.get(dtz, entry.material)?,
+            TablePosition::new(board, flip),
+        ))
+    }
+
+    // get the outcome of a board from -2 for a loss to 2 for a win, from
+    // its table alone
+    //
+    // dead positions such as KvK and KBvK are draws without a table
+    fn probe_wdl_table(&self, board: &Board) -> Option<i32> {
+        if board.is_dead_position() {
+            return Some(0);
+        }
+        let (table, pos) = self.find(board, false)?;
+        match table.probe(&pos, 0)? {
+            Probe::Found(wdl) => Some(wdl),
+            Probe::OtherSide => None,
+        }
+    }
+
+    // get the outcome of a board, and whether the best move is a capture,
+    // or a pawn move when those are searched too
+    fn search(&self, board: &Board, pawn_moves: bool) -> Option<(i32, bool)> {
+        let moves = legal_moves(board);
+        let mut best = -2;
+        let mut count = 0;
+        for &m in &moves {
+            if !(board.is_capture(m) || (pawn_moves && is_pawn_move(board, m))) {
+                continue;
+            }
+            count += 1;
+            let value = -self.search(&board.apply_eval_move(m), false)?.0;
+            if value > best {
+                best = value;
+                if value >= 2 {
+                    return Some((value, true));
+                }
+            }
+        }
+
+        // the tables have no en passant, and a don't care value where a
+        // capture wins, so they are only used when there are other moves
+        let searched_all = count > 0 && count == moves.len();
+        let value = if searched_all {
+            best
+        } else {
+            self.probe_wdl_table(board)?
+        };
+        if best >= value {
+            Some((best, best > 0 || searched_all))
+        } else {
+            Some((value, false))
+        }
+    }
+
+    fn probe_dtz_unchecked(&self, board: &Board) -> Option<i32> {
+        let (wdl, zeroing) = self.search(board, true)?;
+        if wdl == 0 {
+            return Some(0);
+        }
+        if zeroing {
+            return Some(dtz_before_zeroing(wdl));
+        }
+
+        let (table, pos) = self.find(board, true)?;
+        match table.probe(&pos, wdl)? {
+            Probe::Found(dtz) => {
+                let cursed = wdl == 1 || wdl == -1;
+                Some((dtz + if cursed { 100 } else { 0 }) * wdl.signum())
+            }
+            // the table has the other side to move, so the distance comes
+            // from the best move
+            Probe::OtherSide => {
+                let mut best = None;
+                for m in legal_moves(board) {
+                    let zeroing = board.is_capture(m) || is_pawn_move(board, m);
+                    let next = board.apply_eval_move(m);
+                    let mut dtz = if zeroing {
+                        -dtz_before_zeroing(self.search(&next, false)?.0)
+                    } else {
+                        -self.probe_dtz_unchecked(&next)?
+                    };
+                    if dtz == 1 && next.is_checkmate() {
+                        best = Some(1);
+                    }
+                    if !zeroing {
+                        dtz += dtz.signum();
+                    }
+                    // `Option::is_none_or` needs Rust 1.82
+                    #[allow(clippy::unnecessary_map_or)]
+                    let better = best.map_or(true, |best| dtz < best);
+                    if dtz.signum() == wdl.signum() && better {
+                        best = Some(dtz);
+                    }
+                }
+                // a board without moves is checkmate
+                Some(best.unwrap_or(-1))
+            }
+        }
+    }
+}
+
+impl Tablebase for Syzygy {
+    #[inline]
+    fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    fn probe_wdl(&self, board: &Board) -> Option<Wdl> {
+        if !can_probe(board, self.max_pieces) {
+            return None;
+        }
+        Some(match self.search(board, false)?.0 {
+            -2 => Wdl::Loss,
+            -1 => Wdl::BlessedLoss,
+            0 => Wdl::Draw,
+            1 => Wdl::CursedWin,
+            _ => Wdl::Win,
+        })
+    }
+
+    fn probe_dtz(&self, board: &Board) -> Option<i32> {
+        if !can_probe(board, self.max_pieces) {
+            return None;
+        }
+        self.probe_dtz_unchecked(board)
+    }
+}
+
+// the distance to zeroing of a board whose best move is zeroing
+fn dtz_before_zeroing(wdl: i32) -> i32 {
+    match wdl {
+        2 => 1,
+        1 => 101,
+        -1 => -101,
+        -2 => -1,
+        _ => 0,
+    }
+}
+
+fn is_pawn_move(board: &Board, m: Move) -> bool {
+    match m {
+        Move::Piece(from, _) | Move::Promotion(from, _, _) => {
+            board.get_piece(from).is_some_and(|piece| piece.is_pawn())
+        }
+        _ => false,
+    }
+}
+
+// get the legal moves of a board with every promotion, since boards only
+// give the queen promotion but the tables know the others
+fn legal_moves(board: &Board) -> Vec<Move> {
+    let mut result = vec![];
+    for m in board.get_legal_moves() {
+        match m {
+            Move::Piece(from, to)
+                if (to.get_row() == 0 || to.get_row() == 7) && is_pawn_move(board, m) =>
+            {
+                for kind in [
+                    PieceKind::Queen,
+                    PieceKind::Rook,
+                    PieceKind::Bishop,
+                    PieceKind::Knight,
+                ] {
+                    result.push(Move::Promotion(
+                        from,
+                        to,
+                        kind.with(board.get_turn_color(), to),
+                    ));
+                }
+            }
+            m => result.push(m),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::parse_fen;
+    use crate::{BoardBuilder, Piece, Position, TablebaseBoard, Variant, D2, D8, E8};
+
+    // the official KQvK, KRvK and KPvK tables, as listed on
+    // syzygy-tables.info, with both their `.rtbw` and `.rtbz` files
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/syzygy");
+
+    fn tablebase() -> Syzygy {
+        let mut result = Syzygy::new();
+        let count = result.add_directory(FIXTURES).unwrap_or(0);
+        assert_eq!(
+            count, 6,
+            "the KQvK, KRvK and KPvK .rtbw and .rtbz files belong in {}",
+            FIXTURES
+        );
+        result
+    }
+
+    #[test]
+    fn test_material() {
+        for name in ["KQvK", "KRPvKR", "KBNvK", "KvK", "KPPPPPvK"] {
+            assert_eq!(Material::parse(name).unwrap().name(), name);
+        }
+        for name in ["KQ", "KQvQ", "KQQvKK", "kqvk", "KQXvK", "KPPPPPPvK"] {
+            assert_eq!(Material::parse(name), None, "{}", name);
+        }
+        let material = Material::parse("KRPvKR").unwrap();
+        assert_eq!(material.count(), 5);
+        assert_eq!(material.flip().name(), "KRvKRP");
+        assert!(material.has_unique_pieces() && !material.is_symmetric());
+        assert!(Material::parse("KRRvKRR").unwrap().is_symmetric());
+        assert!(!Material::parse("KRRvKBB").unwrap().has_unique_pieces());
+        assert_eq!(material.codes(), vec![1, 4, 6, 12, 14]);
+    }
+
+    #[test]
+    fn test_add_directory() {
+        assert_eq!(Syzygy::new().max_pieces(), 0);
+        let mut tablebase = tablebase();
+        assert_eq!(tablebase.max_pieces(), 3);
+        assert_eq!(tablebase.add_directory(FIXTURES).unwrap(), 0);
+        assert!(tablebase
+            .add_directory(Path::new(FIXTURES).join("missing"))
+            .is_err());
+
+        let paths = std::env::join_paths([FIXTURES, FIXTURES]).unwrap();
+        let tablebase = Syzygy::from_paths(paths.to_str().unwrap()).unwrap();
+        assert_eq!(tablebase.max_pieces(), 3);
+        assert_eq!(Syzygy::from_paths("").unwrap().max_pieces(), 0);
+    }
+
+    #[test]
+    fn test_known_positions() {
+        let tablebase = tablebase();
+        for (fen, wdl, dtz) in [
+            // mate in one, and the mate
+            ("6k1/8/6K1/8/8/8/8/Q7 w - - 0 1", Wdl::Win, 1),
+            ("Q5k1/8/6K1/8/8/8/8/8 b - - 0 1", Wdl::Loss, -1),
+            // the queen or rook is taken
+            ("8/8/8/8/8/8/kQ6/4K3 b - - 0 1", Wdl::Draw, 0),
+            ("8/8/8/8/8/8/k7/R3K3 b - - 0 1", Wdl::Draw, 0),
+            ("k7/8/8/8/8/8/8/1R2K3 w - - 0 1", Wdl::Win, 0),
+            // with the opposition, black holds the draw
+            ("8/4k3/8/4K3/4P3/8/8/8 w - - 0 1", Wdl::Draw, 0),
+            ("8/4k3/8/4K3/4P3/8/8/8 b - - 0 1", Wdl::Loss, 0),
+            // the pawn queens
+            ("8/4P1k1/8/8/8/8/8/K7 w - - 0 1", Wdl::Win, 1),
+            // a king in front of a rook pawn draws
+            ("k7/8/8/8/8/8/P7/K7 w - - 0 1", Wdl::Draw, 0),
+            // the same with the colors the other way around
+            ("8/8/8/8/4p3/4k3/8/4K3 w - - 0 1", Wdl::Loss, 0),
+            ("4k3/Kq6/8/8/8/8/8/8 w - - 0 1", Wdl::Draw, 0),
+        ] {
+            let board = parse_fen(fen).unwrap();
+            assert_eq!(tablebase.probe_wdl(&board), Some(wdl), "{}", fen);
+            let probed = tablebase.probe_dtz(&board).unwrap();
+            if dtz != 0 {
+                assert_eq!(probed, dtz, "{}", fen);
+            }
+            assert_eq!(probed.signum(), (wdl as i32 - 2).signum(), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_probe_every_table() {
+        // every position has the outcome of its best move, and the same
+        // outcome with the board mirrored and the colors swapped
+        let tablebase = tablebase();
+        let square = |index: usize| Position::new((index / 8) as i32, (index % 8) as i32);
+        for kind in [PieceKind::Queen, PieceKind::Rook, PieceKind::Pawn] {
+            for n in (0..2 * 64 * 64 * 64).step_by(997) {
+                let (white_king, piece, black_king) = (n % 64, n / 64 % 64, n / 4096 % 64);
+                if white_king == piece || piece == black_king || black_king == white_king {
+                    continue;
+                }
+                let board = BoardBuilder::default()
+                    .piece(Piece::King(Color::White, square(white_king)))
+                    .piece(kind.with(Color::White, square(piece)))
+                    .piece(Piece::King(Color::Black, square(black_king)))
+                    .set_turn(if n < 64 * 64 * 64 {
+                        Color::White
+                    } else {
+                        Color::Black
+                    })
+                    .build();
+                if board.validate().is_err() {
+                    continue;
+                }
+
+                let wdl = tablebase.probe_wdl(&board).unwrap();
+                let dtz = tablebase.probe_dtz(&board).unwrap();
+                assert_eq!(dtz.signum(), (wdl as i32 - 2).signum(), "{}", board);
+                let best = legal_moves(&board)
+                    .into_iter()
+                    .map(|m| {
+                        let next = board.apply_eval_move(m);
+                        tablebase.probe_wdl(&next).unwrap().negate()
+                    })
+                    .max();
+                let expected = match best {
+                    Some(best) => best,
+                    None if board.is_checkmate() => Wdl::Loss,
+                    None => Wdl::Draw,
+                };
+                assert_eq!(wdl, expected, "{}", board);
+
+                let mirrored = board.mirror();
+                assert_eq!(tablebase.probe_wdl(&mirrored), Some(wdl), "{}", board);
+                assert_eq!(tablebase.probe_dtz(&mirrored), Some(dtz), "{}", board);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_tables() {
+        let dir = std::env::temp_dir().join(format!("chess-engine-syzygy-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let bytes = fs::read(Path::new(FIXTURES).join("KQvK.rtbw"))
+            .expect("the official KQvK.rtbw belongs in the fixtures");
+        fs::write(dir.join("KQvK.rtbw"), &bytes[..bytes.len() / 2]).unwrap();
+        fs::write(dir.join("KRvK.rtbw"), &bytes).unwrap();
+        fs::write(dir.join("KBBvK.rtbw"), [0x71, 0xe8, 0x23, 0x5d, 0, 0, 0xff]).unwrap();
+        fs::write(dir.join("KQvK.txt"), "").unwrap();
+        fs::write(dir.join("kqvk.rtbz"), "").unwrap();
+
+        let mut tablebase = Syzygy::new();
+        assert_eq!(tablebase.add_directory(&dir).unwrap(), 3);
+        for fen in [
+            "6k1/8/6K1/8/8/8/8/Q7 w - - 0 1",
+            "6k1/8/6K1/8/8/8/8/R7 w - - 0 1",
+            "6k1/8/6K1/8/8/8/8/BB6 w - - 0 1",
+        ] {
+            let board = parse_fen(fen).unwrap();
+            assert_eq!(tablebase.probe_wdl(&board), None, "{}", fen);
+            assert_eq!(tablebase.probe_dtz(&board), None, "{}", fen);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_tablebase_board() {
+        let tablebase = tablebase();
+
+        // taking the rook reaches a won position the tablebase has
+        let board = parse_fen("3k4/8/8/8/8/8/3r4/3QK3 w - - 0 1").unwrap();
+        let searcher = TablebaseBoard::new(&tablebase, board, board);
+        assert_eq!(searcher.probe_wdl(&board), None);
+        let (m, _, value) = searcher.get_best_next_move(1);
+        assert!(matches!(m, Some(Move::Piece(_, D2))), "{:?}", m);
+        assert!(value > Wdl::CursedWin.value());
+
+        // in the tablebase, both sides play the quickest win and the
+        // slowest loss until the queen mates
+        let mut board = parse_fen("8/8/8/3k4/8/8/8/K5Q1 w - - 0 1").unwrap();
+        let mut dtz = tablebase.probe_dtz(&board).unwrap();
+        assert!(dtz > 2);
+        while !board.is_checkmate() {
+            let (m, _, _) = TablebaseBoard::new(&tablebase, board, board).get_best_next_move(1);
+            board = board.apply_eval_move(m.unwrap());
+            let next = tablebase.probe_dtz(&board).unwrap();
+            assert!(
+                next == -(dtz.abs() - 1) * dtz.signum() || board.is_checkmate(),
+                "{}",
+                board
+            );
+            dtz = next;
+        }
+        assert_eq!(board.get_turn_color(), Color::Black);
+
+        // with the king in front of its pawn, white keeps the win
+        let board = parse_fen("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1").unwrap();
+        let board = board.apply_eval_move(Move::Piece(E8, D8));
+        let (m, _, _) = TablebaseBoard::new(&tablebase, board, board).get_best_next_move(2);
+        let next = board.apply_eval_move(m.unwrap());
+        assert_eq!(tablebase.probe_wdl(&next), Some(Wdl::Loss), "{:?}", m);
+
+        // a win the fifty move rule stops is worth little more than a draw
+        let board = parse_fen("8/8/8/3k4/8/8/8/K5Q1 w - - 95 1").unwrap();
+        let (_, _, value) = TablebaseBoard::new(&tablebase, board, board).get_best_next_move(1);
+        assert_eq!(value, Wdl::CursedWin.value());
+
+        // regular chess without castling rights only
+        let board = parse_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        assert_eq!(tablebase.probe_wdl(&board), None);
+        assert_eq!(
+            TablebaseBoard::new(&tablebase, board, board).probe_wdl(&board),
+            None
+        );
+        let board = parse_fen("4k3/8/8/8/8/8/8/4K2R w - - 0 1").unwrap();
+        assert_eq!(tablebase.probe_wdl(&board), Some(Wdl::Win));
+        let board = board.set_variant(Variant::Atomic);
+        assert_eq!(tablebase.probe_wdl(&board), None);
+        assert_eq!(
+            TablebaseBoard::new(&tablebase, board, board).probe_wdl(&board),
+            None
+        );
+    }
+}
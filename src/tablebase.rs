@@ -0,0 +1,246 @@
+//! Endgame tablebases, used to play small endgames perfectly.
+//!
+//! A tablebase knows the outcome of every position with a few pieces left,
+//! under perfect play. `TablebaseBoard` puts one in front of an evaluator:
+//! the search stops at positions the tablebase has and takes their exact
+//! outcome instead of the static evaluation. Wins that reach a capture or
+//! pawn move sooner are worth more, so the search makes progress instead of
+//! shuffling between won positions.
+//!
+//! The outcomes follow Syzygy tablebases, which count the fifty move rule
+//! when telling wins from draws. Readers for tablebase files implement the
+//! `Tablebase` trait, such as `Syzygy` with the `syzygy` feature.
+use alloc::vec::Vec;
+
+use crate::{Board, Color, Evaluate, Move, Variant};
+
+/// The outcome of a position for the current player, from a tablebase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Wdl {
+    /// The current player loses.
+    Loss,
+    /// The current player loses, unless the fifty move rule saves them.
+    BlessedLoss,
+    /// Neither player can win.
+    Draw,
+    /// The current player wins, unless the fifty move rule saves the
+    /// opponent.
+    CursedWin,
+    /// The current player wins.
+    Win,
+}
+
+impl Wdl {
+    /// Get the outcome for the other player.
+    #[inline]
+    pub fn negate(self) -> Self {
+        match self {
+            Self::Loss => Self::Win,
+            Self::BlessedLoss => Self::CursedWin,
+            Self::Draw => Self::Draw,
+            Self::CursedWin => Self::BlessedLoss,
+            Self::Win => Self::Loss,
+        }
+    }
+
+    // the value of the outcome in the search, less than a checkmate so
+    // that a mate the search can see is still preferred
+    pub(crate) fn value(self) -> f64 {
+        match self {
+            Self::Loss => -TABLEBASE_WIN,
+            Self::BlessedLoss => -1.0,
+            Self::Draw => 0.0,
+            Self::CursedWin => 1.0,
+            Self::Win => TABLEBASE_WIN,
+        }
+    }
+}
+
+const TABLEBASE_WIN: f64 = 100000.0;
+
+/// A source of exact endgame outcomes, such as a directory of Syzygy files.
+pub trait Tablebase {
+    /// Get the most pieces, kings included, of any position the tablebase
+    /// has.
+    fn max_pieces(&self) -> usize;
+
+    /// Get the outcome of a position for the current player, if the
+    /// tablebase has it.
+    fn probe_wdl(&self, board: &Board) -> Option<Wdl>;
+
+    /// Get the distance to zeroing (DTZ) of a position: the number of plies
+    /// until a capture or pawn move that keeps the outcome, which is
+    /// positive when the current player wins and negative when they lose.
+    /// Wins and losses that the fifty move rule turns into draws are 100
+    /// plies further away.
+    ///
+    /// Tablebases without distances return `None`, which is the default.
+    fn probe_dtz(&self, board: &Board) -> Option<i32> {
+        let _ = board;
+        None
+    }
+}
+
+// tablebases only cover regular chess without castling rights
+pub(crate) fn can_probe(board: &Board, max_pieces: usize) -> bool {
+    let castling = [Color::White, Color::Black].into_iter().any(|color| {
+        let rights = board.get_castling_rights(color);
+        rights.can_kingside_castle() || rights.can_queenside_castle()
+    });
+    board.get_variant() == Variant::Standard && !castling && board.pieces().count() <= max_pieces
+}
+
+/// An evaluator with a tablebase in front of it, created with
+/// `TablebaseBoard::new`.
+///
+/// The board is kept next to the evaluator so it can be looked up in the
+/// tablebase, and both follow the moves of the search.
+#[derive(Clone, Copy, Debug)]
+pub struct TablebaseBoard<'a, T, E> {
+    tablebase: &'a T,
+    board: Board,
+    evaluator: E,
+    is_root: bool,
+}
+
+impl<'a, T: Tablebase, E: Evaluate> TablebaseBoard<'a, T, E> {
+    /// Put a tablebase in front of an evaluator for the given board.
+    pub fn new(tablebase: &'a T, board: Board, evaluator: E) -> Self {
+        Self {
+            tablebase,
+            board,
+            evaluator,
+            is_root: true,
+        }
+    }
+
+    /// Get the board being evaluated.
+    #[inline]
+    pub fn get_board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Get the outcome of a board, if the tablebase has it.
+    ///
+    /// Tablebases only cover regular chess without castling rights, so
+    /// other positions are never looked up.
+    pub fn probe_wdl(&self, board: &Board) -> Option<Wdl> {
+        if !can_probe(board, self.tablebase.max_pieces()) {
+            return None;
+        }
+        self.tablebase.probe_wdl(board)
+    }
+
+    // the value of a board the tablebase has, for its current player: a
+    // win is worth less the further away its capture or pawn move is, and
+    // a loss more, down to a cursed win or blessed loss when the fifty
+    // move rule comes first
+    fn tablebase_value(&self, board: &Board) -> Option<f64> {
+        let wdl = self.probe_wdl(board)?;
+        let dtz = match wdl {
+            Wdl::Draw => None,
+            _ => self.tablebase.probe_dtz(board),
+        };
+        let dtz = match dtz {
+            Some(dtz) => dtz,
+            None => return Some(wdl.value()),
+        };
+
+        let clock = board.get_halfmove_clock() as i32;
+        if dtz.abs() + clock > 100 {
+            let wdl = if dtz > 0 {
+                Wdl::CursedWin
+            } else {
+                Wdl::BlessedLoss
+            };
+            return Some(wdl.value());
+        }
+        // a capture or pawn move that was just played counts as the
+        // zeroing move, and only a checkmate is better
+        let distance = if dtz == -1 && board.is_checkmate() {
+            -1
+        } else if clock == 0 {
+            0
+        } else {
+            dtz.abs()
+        };
+        Some(if dtz > 0 {
+            TABLEBASE_WIN - distance as f64
+        } else {
+            distance as f64 - TABLEBASE_WIN
+        })
+    }
+
+    // the tablebase value for a color, below the root of the search
+    fn value_from_tablebase(&self, color: Color) -> Option<f64> {
+        if self.is_root {
+            return None;
+        }
+        let value = self.tablebase_value(&self.board)?;
+        Some(if color == self.get_current_player_color() {
+            value
+        } else {
+            -value
+        })
+    }
+}
+
+impl<'a, T: Tablebase, E: Evaluate> Evaluate for TablebaseBoard<'a, T, E> {
+    #[inline]
+    fn value_for(&self, color: Color) -> f64 {
+        self.value_from_tablebase(color)
+            .unwrap_or_else(|| self.evaluator.value_for(color))
+    }
+
+    #[inline]
+    fn get_current_player_color(&self) -> Color {
+        self.board.get_turn_color()
+    }
+
+    // the search ends at positions the tablebase has, which are then
+    // valued by `get_no_moves_value`, except at the root, which always
+    // has its moves searched
+    fn get_legal_moves(&self) -> Vec<Move> {
+        if !self.is_root && self.probe_wdl(&self.board).is_some() {
+            vec![]
+        } else {
+            self.evaluator.get_legal_moves()
+        }
+    }
+
+    fn get_no_moves_value(&self, color: Color) -> f64 {
+        self.value_from_tablebase(color)
+            .unwrap_or_else(|| self.evaluator.get_no_moves_value(color))
+    }
+
+    #[inline]
+    fn apply_eval_move(&self, m: Move) -> Self {
+        Self {
+            tablebase: self.tablebase,
+            board: self.board.apply_eval_move(m),
+            evaluator: self.evaluator.apply_eval_move(m),
+            is_root: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wdl() {
+        assert!(Wdl::Win > Wdl::CursedWin && Wdl::CursedWin > Wdl::Draw);
+        assert!(Wdl::Draw > Wdl::BlessedLoss && Wdl::BlessedLoss > Wdl::Loss);
+        for wdl in [
+            Wdl::Loss,
+            Wdl::BlessedLoss,
+            Wdl::Draw,
+            Wdl::CursedWin,
+            Wdl::Win,
+        ] {
+            assert_eq!(wdl.negate().negate(), wdl);
+            assert_eq!(wdl.negate().value(), -wdl.value());
+        }
+    }
+}
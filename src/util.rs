@@ -2,18 +2,47 @@ use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 
 use crate::board::{Board, BoardBuilder};
-use crate::piece::Piece;
+use crate::piece::{Piece, PieceKind};
 use crate::position::Position;
-use crate::{Color, Evaluate, Move};
-
-// generate FEN
-// cannot calculate halfmove or fullmove based on board state,
-// so they must be provided
-pub fn format_fen(
-    board: &Board,
-    halfmove_clock: u8,
-    fullmove_number: u8,
-) -> Result<String, String> {
+use crate::{
+    Color, EpdError, Evaluate, FenError, IllegalMoveReason, Move, MoveParseError, PgnError,
+    SanError, Variant,
+};
+
+// generate FEN, with all six fields
+//
+// Chess960 boards write their castling rights in X-FEN, which only names
+// the file of a castling rook when it is not the outermost rook on its side
+pub fn format_fen(board: &Board) -> Result<String, FenError> {
+    format_fen_with(board, false)
+}
+
+// generate Shredder-FEN, which always writes castling rights
+// as the files of the castling rooks, such as `HAha`
+pub fn format_shredder_fen(board: &Board) -> Result<String, FenError> {
+    format_fen_with(board, true)
+}
+
+// find the outermost rook on one side of a player's king, which is
+// the rook that `K` and `Q` refer to in X-FEN
+fn find_outer_rook_col(board: &Board, color: Color, kingside: bool) -> Option<i32> {
+    let row = Position::king_pos(color).get_row();
+    let king_col = board
+        .get_king_pos(color)
+        .filter(|pos| pos.get_row() == row)?
+        .get_col();
+    let is_rook = |col: &i32| {
+        let pos = Position::new(row, *col);
+        board.get_piece(pos) == Some(Piece::Rook(color, pos))
+    };
+    if kingside {
+        (king_col + 1..8).rev().find(is_rook)
+    } else {
+        (0..king_col).find(is_rook)
+    }
+}
+
+fn format_fen_with(board: &Board, shredder: bool) -> Result<String, FenError> {
     let mut fen: Vec<String> = vec![];
 
     // placement
@@ -43,6 +72,9 @@ pub fn format_fen(
                         Color::White => ch.to_uppercase(),
                         Color::Black => ch,
                     });
+                    if board.is_promoted(Position::new(row, col)) {
+                        fen.push("~".to_string());
+                    }
                 }
             };
         }
@@ -55,6 +87,22 @@ pub fn format_fen(
         }
     }
 
+    // crazyhouse pockets, white's pieces first
+    if board.get_variant() == Variant::Crazyhouse {
+        fen.push("[".to_string());
+        for color in [Color::White, Color::Black] {
+            for kind in PieceKind::ALL {
+                for _ in 0..board.get_pocket_count(color, kind) {
+                    fen.push(match color {
+                        Color::White => kind.get_letter().to_string(),
+                        Color::Black => kind.get_letter().to_ascii_lowercase().to_string(),
+                    });
+                }
+            }
+        }
+        fen.push("]".to_string());
+    }
+
     // active color
     fen.push(" ".to_string());
     fen.push(
@@ -68,23 +116,30 @@ pub fn format_fen(
     // castling
     fen.push(" ".to_string());
     let mut any_castling_rights = false;
-    let white_castling_rights = board.get_castling_rights(Color::White);
-    if white_castling_rights.can_kingside_castle() {
-        fen.push("K".to_string());
-        any_castling_rights = true;
-    }
-    if white_castling_rights.can_queenside_castle() {
-        fen.push("Q".to_string());
-        any_castling_rights = true
-    }
-    let black_castling_rights = board.get_castling_rights(Color::Black);
-    if black_castling_rights.can_kingside_castle() {
-        fen.push("k".to_string());
-        any_castling_rights = true;
-    }
-    if black_castling_rights.can_queenside_castle() {
-        fen.push("q".to_string());
-        any_castling_rights = true
+    for color in [Color::White, Color::Black] {
+        let castling_rights = board.get_castling_rights(color);
+        for (col, kingside) in [
+            (castling_rights.get_kingside_rook_col(), true),
+            (castling_rights.get_queenside_rook_col(), false),
+        ] {
+            if let Some(col) = col {
+                let ch = if shredder
+                    || (board.is_chess960()
+                        && find_outer_rook_col(board, color, kingside) != Some(col))
+                {
+                    (b'a' + col as u8) as char
+                } else if kingside {
+                    'k'
+                } else {
+                    'q'
+                };
+                fen.push(match color {
+                    Color::White => ch.to_ascii_uppercase().to_string(),
+                    Color::Black => ch.to_string(),
+                });
+                any_castling_rights = true;
+            }
+        }
     }
     if !any_castling_rights {
         fen.push("-".to_string());
@@ -102,50 +157,109 @@ pub fn format_fen(
 
     // half move
     fen.push(" ".to_string());
-    let halfmove_clock = halfmove_clock.to_string();
-    fen.push(halfmove_clock);
+    fen.push(board.get_halfmove_clock().to_string());
 
     // full move
     fen.push(" ".to_string());
-    let fullmove_number = fullmove_number.to_string();
-    fen.push(fullmove_number);
+    fen.push(board.get_fullmove_number().to_string());
 
     Ok(fen.join(""))
 }
 
 // parse Forsyth–Edwards Notation (FEN) board state
 // more direct than replaying every move from PGN
-pub fn parse_fen(fen: &str) -> Result<Board, String> {
-    let mut parts = fen.split_ascii_whitespace();
-    // fen has six parts
-    let placement = parts.next();
-    let active_color = parts.next();
-    let castling = parts.next();
-    let en_passant = parts.next();
-    let _halfmove_clock = parts.next();
-    let _fullmove_number = parts.next();
-    // make sure all parts present
-    if placement.is_none()
-        || active_color.is_none()
-        || castling.is_none()
-        || en_passant.is_none()
-        // ignore half move and full move for now
-        // || halfmove_clock.is_none()
-        // || fullmove_number.is_none()
-        // extra part
-        || parts.next().is_some()
-    {
-        return Err(String::from("wrong number of spaces"));
-    }
+//
+// the position must be legal, see `Board::validate`
+pub fn parse_fen(fen: &str) -> Result<Board, FenError> {
+    let board = parse_fen_lenient(fen)?;
+    board.validate()?;
+    Ok(board)
+}
+
+// parse FEN without checking that the position is legal,
+// for setting up puzzles, variants, and test positions
+//
+// the halfmove clock and fullmove number may be left out together,
+// as in EPD, in which case they start at 0 and 1
+pub fn parse_fen_lenient(fen: &str) -> Result<Board, FenError> {
+    let parts: Vec<&str> = fen.split_ascii_whitespace().collect();
+    let (placement, active_color, castling, en_passant, clocks) = match parts[..] {
+        [placement, active_color, castling, en_passant] => {
+            (placement, active_color, castling, en_passant, None)
+        }
+        [placement, active_color, castling, en_passant, halfmove_clock, fullmove_number] => (
+            placement,
+            active_color,
+            castling,
+            en_passant,
+            Some((halfmove_clock, fullmove_number)),
+        ),
+        _ => return Err(FenError::WrongFieldCount),
+    };
 
     let mut builder = BoardBuilder::default();
+    if let Some((halfmove_clock, fullmove_number)) = clocks {
+        builder = builder
+            .set_halfmove_clock(
+                halfmove_clock
+                    .parse()
+                    .map_err(|_| FenError::InvalidHalfmoveClock)?,
+            )
+            .set_fullmove_number(
+                fullmove_number
+                    .parse()
+                    .ok()
+                    .filter(|number| *number > 0)
+                    .ok_or(FenError::InvalidFullmoveNumber)?,
+            );
+    }
+
+    // crazyhouse pockets follow the placement, either in brackets
+    // like `RNBQKBNR[Qn]`, or as a ninth rank like `RNBQKBNR/Qn`
+    let mut placement = placement;
+    let mut pocket = None;
+    if let Some((board_part, pocket_part)) = placement.split_once('[') {
+        placement = board_part;
+        pocket = Some(
+            pocket_part
+                .strip_suffix(']')
+                .ok_or(FenError::UnclosedPocket)?,
+        );
+    } else if placement.matches('/').count() == 8 {
+        let (board_part, pocket_part) = placement.rsplit_once('/').unwrap();
+        placement = board_part;
+        pocket = Some(pocket_part);
+    }
+    if let Some(pocket) = pocket {
+        let mut counts = [[0u8; 6]; 2];
+        for c in pocket.chars() {
+            let kind = match PieceKind::from_letter(c) {
+                Some(kind) if kind != PieceKind::King => kind,
+                _ => return Err(FenError::InvalidPocketPiece(c)),
+            };
+            let color = match c.is_uppercase() {
+                true => Color::White,
+                false => Color::Black,
+            };
+            let count = &mut counts[color as usize][kind as usize];
+            *count = count.checked_add(1).ok_or(FenError::InvalidPocket)?;
+        }
+        for color in [Color::White, Color::Black] {
+            for kind in PieceKind::ALL {
+                builder = builder.pocket(color, kind, counts[color as usize][kind as usize]);
+            }
+        }
+        builder = builder.set_variant(Variant::Crazyhouse);
+    }
 
     // parse placement (from white's perspective)
     let mut row: i32 = 7;
     let mut col: i32 = 0;
-    for c in placement.unwrap().chars() {
-        if (col > 7 && c != '/') || row < 0 {
-            return Err(String::from("too many pieces"));
+    for c in placement.chars() {
+        if row < 0 {
+            return Err(FenError::TooManyRanks);
+        } else if col > 7 && c != '/' {
+            return Err(FenError::RankTooLong);
         }
         match c {
             x if x.is_alphabetic() => {
@@ -154,7 +268,7 @@ pub fn parse_fen(fen: &str) -> Result<Board, String> {
                     false => Color::Black,
                 };
                 // using as because row/col always in u8 range
-                let position = Position::new(row as i32, col as i32);
+                let position = Position::new(row, col);
                 let piece = match x.to_ascii_lowercase() {
                     'b' => Piece::Bishop(color, position),
                     'n' => Piece::Knight(color, position),
@@ -163,40 +277,56 @@ pub fn parse_fen(fen: &str) -> Result<Board, String> {
                     'p' => Piece::Pawn(color, position),
                     'r' => Piece::Rook(color, position),
                     _ => {
-                        return Err(String::from("unexpected piece"));
+                        return Err(FenError::InvalidPiece(x));
                     }
                 };
                 builder = builder.piece(piece);
                 col += 1;
             }
-            x if x.is_numeric() => {
+            x @ '1'..='9' => {
                 // skip squares
-                let skip = x.to_digit(10).unwrap();
-                col += skip as i32;
+                col += x as i32 - '0' as i32;
+                if col > 8 {
+                    return Err(FenError::RankTooLong);
+                }
             }
             '/' => {
                 if col != 8 {
-                    return Err(String::from("incomplete row"));
+                    return Err(FenError::RankTooShort);
                 }
                 col = 0;
                 row -= 1;
             }
-            _ => {}
+            // the piece before was promoted, in crazyhouse
+            '~' if col > 0 => {
+                builder = builder.promoted(Position::new(row, col - 1));
+            }
+            x => return Err(FenError::InvalidPiece(x)),
         };
     }
-    if col != 8 && row != 0 {
-        return Err(String::from("incomplete position"));
+    if row > 0 {
+        return Err(FenError::MissingRanks);
+    } else if row < 0 {
+        return Err(FenError::TooManyRanks);
+    } else if col != 8 {
+        return Err(FenError::RankTooShort);
     }
 
-    builder = builder.set_turn(match active_color.unwrap() {
+    builder = builder.set_turn(match active_color {
         "b" => Color::Black,
         "w" => Color::White,
         _ => {
-            return Err(String::from("invalid active color"));
+            return Err(FenError::InvalidActiveColor);
         }
     });
 
-    match castling.unwrap() {
+    // castling rights are read as X-FEN, which also accepts Shredder-FEN:
+    // `K` and `Q` refer to the outermost rook on that side of the king,
+    // and a file letter refers to the rook on that file
+    let board = builder.build();
+    builder = BoardBuilder::from(board);
+    let mut chess960 = false;
+    match castling {
         "-" => {}
         castling => {
             for c in castling.chars() {
@@ -204,29 +334,41 @@ pub fn parse_fen(fen: &str) -> Result<Board, String> {
                     true => Color::White,
                     false => Color::Black,
                 };
-                match c.to_ascii_lowercase() {
-                    'k' => {
-                        builder = builder.enable_kingside_castle(color);
-                    }
-                    'q' => {
-                        builder = builder.enable_queenside_castle(color);
+                let (kingside, col) = match c.to_ascii_lowercase() {
+                    'k' => (true, find_outer_rook_col(&board, color, true).unwrap_or(7)),
+                    'q' => (
+                        false,
+                        find_outer_rook_col(&board, color, false).unwrap_or(0),
+                    ),
+                    file @ 'a'..='h' => {
+                        let col = file as i32 - 'a' as i32;
+                        let king_col = board
+                            .get_king_pos(color)
+                            .map(|pos| pos.get_col())
+                            .unwrap_or(4);
+                        (col > king_col, col)
                     }
                     _ => {
-                        return Err(String::from("invalid castling side"));
+                        return Err(FenError::InvalidCastling(c));
                     }
                 };
+                if col != if kingside { 7 } else { 0 }
+                    || board.get_king_pos(color).map(|pos| pos.get_col()) != Some(4)
+                {
+                    chess960 = true;
+                }
+                builder = match kingside {
+                    true => builder.set_kingside_castle_rook(color, col),
+                    false => builder.set_queenside_castle_rook(color, col),
+                };
             }
         }
     };
+    builder = builder.set_chess960(chess960);
 
-    builder = builder.set_en_passant(match en_passant.unwrap() {
+    builder = builder.set_en_passant(match en_passant {
         "-" => None,
-        some => match Position::pgn(some) {
-            Ok(position) => Some(position),
-            _ => {
-                return Err(String::from("invalid en passant"));
-            }
-        },
+        some => Some(Position::pgn(some).map_err(FenError::InvalidEnPassant)?),
     });
 
     Ok(builder.build())
@@ -236,13 +378,23 @@ pub fn parse_fen(fen: &str) -> Result<Board, String> {
 //
 // move parsing is strict and should not include any +, #, etc characters.
 //
-// returns Err("ambiguous") if there are multiple possibilities
+// returns Err(AmbiguousMove) if there are multiple possibilities
 // e.g. "Nxc4" when there are N at e2 and d6
 // the move should instead specify which N ("Nexc4" or "Ne2xc4")
 //
-// returns Err(InvalidMove) if there is a parse error or the move is
-// not valid based on the current board position and turn.
-pub fn parse_san_move(board: &Board, move_str: &str) -> Result<Move, String> {
+// returns Err(IllegalMove) with the reason if a matching piece could
+// make the move but the rules forbid it, such as when it is pinned,
+// and Err(NoMatchingMove) if no piece could make the move at all.
+pub fn parse_san_move(board: &Board, move_str: &str) -> Result<Move, SanError> {
+    // crazyhouse drops, such as "N@f3", or "@f3" for a pawn
+    if move_str.contains('@') {
+        let m = Move::try_from(move_str.to_string()).map_err(SanError::InvalidDrop)?;
+        return board
+            .check_move(m)
+            .map(|_| m)
+            .map_err(SanError::IllegalMove);
+    }
+
     if move_str == "0-0" {
         return Ok(Move::KingSideCastle {});
     } else if move_str == "0-0-0" {
@@ -273,12 +425,7 @@ pub fn parse_san_move(board: &Board, move_str: &str) -> Result<Move, String> {
     let to: String = vec![chars.next_back().unwrap_or(' '), last.unwrap_or(' ')]
         .into_iter()
         .collect();
-    let move_to = match Position::pgn(&to) {
-        Ok(position) => position,
-        Err(_) => {
-            return Err("invalid to position".to_string());
-        }
-    };
+    let move_to = Position::pgn(&to).map_err(SanError::InvalidSquare)?;
 
     // chars is now source, with possible 'x' take at end
     let mut source_column = chars.next();
@@ -349,17 +496,495 @@ pub fn parse_san_move(board: &Board, move_str: &str) -> Result<Move, String> {
         }
     }
 
+    let to_move = |from| match move_promotion {
+        None => Move::Piece(from, move_to),
+        Some(piece) => Move::Promotion(from, move_to, piece.get_kind().with(color, move_to)),
+    };
     match candidates.len() {
-        0 => Err("no matching move".to_string()),
-        1 => {
-            let move_from = candidates[0].get_pos();
-            match move_promotion {
-                None => Ok(Move::Piece(move_from, move_to)),
-                Some(piece) => Ok(Move::Promotion(move_from, move_to, piece)),
+        0 => {
+            // explain why a matching piece that could reach the
+            // target square is not allowed to move there
+            let reason = board
+                .pieces_of_kind(piece.get_color(), piece.get_kind())
+                .map(|piece| piece.get_pos())
+                .filter(|pos| column.is_none() || column == Some(pos.get_col()))
+                .filter(|pos| row.is_none() || row == Some(pos.get_row()))
+                .filter_map(|pos| board.check_move(to_move(pos)).err())
+                .find(|reason| *reason != IllegalMoveReason::InvalidMovement);
+            match reason {
+                Some(reason) => Err(SanError::IllegalMove(reason)),
+                None => Err(SanError::NoMatchingMove),
+            }
+        }
+        1 => Ok(to_move(candidates[0].get_pos())),
+        _ => Err(SanError::AmbiguousMove),
+    }
+}
+
+// parse a move in SAN as it is usually written in PGN and EPD, which
+// may be followed by check and annotation marks, and may write castling
+// with the letter O and promotions with `=`, such as "O-O+" or "e8=Q#"
+pub fn parse_annotated_san_move(board: &Board, move_str: &str) -> Result<Move, SanError> {
+    let move_str = move_str
+        .trim()
+        .trim_end_matches(['+', '#', '!', '?'])
+        .replace('=', "")
+        .replace('O', "0");
+    parse_san_move(board, &move_str)
+}
+
+// parse a move in UCI (long algebraic) notation, such as "e2e4", "e7e8q",
+// "N@f3" for a crazyhouse drop, or "0000" for a null move
+//
+// castling is written as the king's move, such as "e1g1", or in Chess960
+// as the king capturing its own rook, such as "e1h1", so the board is
+// needed to tell it apart from a regular king move. the move is not
+// checked for legality
+pub fn parse_uci_move(board: &Board, move_str: &str) -> Result<Move, MoveParseError> {
+    let move_str = move_str.trim();
+    if move_str == "0000" {
+        return Ok(Move::Null);
+    }
+    if move_str.contains('@') {
+        return Move::try_from(move_str.to_string());
+    }
+    if !move_str.is_ascii() || !(4..=5).contains(&move_str.len()) {
+        return Err(MoveParseError::InvalidFormat);
+    }
+
+    let from = Position::pgn(&move_str[..2])?;
+    let to = Position::pgn(&move_str[2..4])?;
+    match move_str[4..].chars().next() {
+        None => Ok(board
+            .get_castling_move(from, to)
+            .unwrap_or(Move::Piece(from, to))),
+        Some(c) if c.is_ascii_lowercase() => match PieceKind::from_letter(c) {
+            Some(PieceKind::Pawn) | None => Err(MoveParseError::InvalidPromotion),
+            Some(kind) => Ok(Move::Promotion(
+                from,
+                to,
+                kind.with(board.get_turn_color(), to),
+            )),
+        },
+        Some(_) => Err(MoveParseError::InvalidPromotion),
+    }
+}
+
+// format a move in UCI (long algebraic) notation, the inverse of
+// `parse_uci_move`
+//
+// castling is written with the squares from `Board::get_castling_squares`,
+// or as "O-O" and "O-O-O" if the current player has no king to castle with
+pub fn format_uci_move(board: &Board, m: Move) -> String {
+    match m {
+        Move::Piece(from, to) => format!("{}{}", from, to),
+        Move::Promotion(from, to, piece) => format!(
+            "{}{}{}",
+            from,
+            to,
+            piece.get_kind().get_letter().to_ascii_lowercase()
+        ),
+        Move::Drop(kind, pos) => format!("{}@{}", kind.get_letter(), pos),
+        Move::KingSideCastle | Move::QueenSideCastle => match board.get_castling_squares(m) {
+            Some((from, to)) => format!("{}{}", from, to),
+            None => m.to_string(),
+        },
+        Move::Null => "0000".to_string(),
+    }
+}
+
+// a position from an EPD (Extended Position Description) record, as used
+// by test suites such as WAC, ECM and STS, along with its operations
+#[derive(Clone, Debug, PartialEq)]
+pub struct Epd {
+    board: Board,
+    operations: Vec<(String, Vec<String>)>,
+    best_moves: Vec<Move>,
+    avoid_moves: Vec<Move>,
+}
+
+impl Epd {
+    pub fn get_board(&self) -> &Board {
+        &self.board
+    }
+
+    // the operands of an operation, such as ["Qxf7+"] for `bm Qxf7+;`,
+    // or None if the record does not have the operation
+    pub fn get_operands(&self, opcode: &str) -> Option<&[String]> {
+        self.operations
+            .iter()
+            .find(|(name, _)| name == opcode)
+            .map(|(_, operands)| operands.as_slice())
+    }
+
+    fn get_first_operand(&self, opcode: &str) -> Option<&str> {
+        self.get_operands(opcode)?.first().map(String::as_str)
+    }
+
+    // the name of the position, from `id`
+    pub fn get_id(&self) -> Option<&str> {
+        self.get_first_operand("id")
+    }
+
+    // the comment on the position, from `c0`
+    pub fn get_comment(&self) -> Option<&str> {
+        self.get_first_operand("c0")
+    }
+
+    // the best moves, from `bm`
+    pub fn get_best_moves(&self) -> &[Move] {
+        &self.best_moves
+    }
+
+    // the moves to avoid, from `am`
+    pub fn get_avoid_moves(&self) -> &[Move] {
+        &self.avoid_moves
+    }
+
+    // the depth the position was analyzed to, from `acd`
+    pub fn get_analysis_depth(&self) -> Option<u32> {
+        self.get_first_operand("acd")?.parse().ok()
+    }
+
+    // the halfmove clock, from `hmvc`
+    pub fn get_halfmove_clock(&self) -> Option<u16> {
+        self.get_first_operand("hmvc")?.parse().ok()
+    }
+
+    // the fullmove number, from `fmvn`
+    pub fn get_fullmove_number(&self) -> Option<u16> {
+        self.get_first_operand("fmvn")?.parse().ok()
+    }
+
+    // the evaluation of the position in centipawns, from `ce`
+    pub fn get_centipawn_eval(&self) -> Option<i32> {
+        self.get_first_operand("ce")?.parse().ok()
+    }
+
+    // does a move solve the position? it must be one of the best moves,
+    // and none of the moves to avoid
+    //
+    // moves are compared by their squares and the kind of piece they
+    // promote to, so a queen promotion matches the plain move to the
+    // last rank that the engine plays
+    pub fn is_solved_by(&self, m: Move) -> bool {
+        let m = normalize_promotion(m);
+        let same = |other: &Move| match (m, *other) {
+            (Move::Promotion(from, to, piece), Move::Promotion(other_from, other_to, other)) => {
+                from == other_from && to == other_to && piece.get_kind() == other.get_kind()
+            }
+            (m, other) => m == other,
+        };
+        (self.best_moves.is_empty() || self.best_moves.iter().any(same))
+            && !self.avoid_moves.iter().any(same)
+    }
+}
+
+// write a queen promotion as the engine plays it, as a plain move to the
+// last rank, and other promotions with the piece on the promotion square
+fn normalize_promotion(m: Move) -> Move {
+    match m {
+        Move::Promotion(from, to, piece) if piece.is_queen() => Move::Piece(from, to),
+        Move::Promotion(from, to, piece) => {
+            Move::Promotion(from, to, piece.get_kind().with(piece.get_color(), to))
+        }
+        m => m,
+    }
+}
+
+// split EPD operations such as `bm Qxf7+ Nf6; id "WAC.001";` into their
+// opcodes and operands, keeping quoted operands whole
+fn parse_epd_operations(operations: &str) -> Result<Vec<(String, Vec<String>)>, EpdError> {
+    let mut result = vec![];
+    let mut words: Vec<String> = vec![];
+    let mut chars = operations.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ';' => {
+                if !words.is_empty() {
+                    let opcode = words.remove(0);
+                    result.push((opcode, core::mem::take(&mut words)));
+                }
+            }
+            '"' => {
+                if words.is_empty() {
+                    return Err(EpdError::MissingOpcode);
+                }
+                let mut word = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(EpdError::UnclosedString),
+                    }
+                }
+                words.push(word);
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut word = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != ';') {
+                    word.push(c);
+                }
+                words.push(word);
+            }
+        }
+    }
+    // the last operation may leave out its semicolon
+    if !words.is_empty() {
+        let opcode = words.remove(0);
+        result.push((opcode, words));
+    }
+    Ok(result)
+}
+
+// parse an EPD record: the first four fields of a FEN, followed by
+// operations ending in semicolons
+//
+// the moves of `bm` and `am` operations are checked against the position,
+// with queen promotions written as plain moves to the last rank, and the
+// `hmvc` and `fmvn` operations set the board's move counters
+pub fn parse_epd(epd: &str) -> Result<Epd, EpdError> {
+    let mut rest = epd.trim();
+    let mut fields = vec![];
+    for _ in 0..4 {
+        let (field, remaining) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        fields.push(field);
+        rest = remaining.trim_start();
+    }
+    let board = parse_fen(&fields.join(" ")).map_err(EpdError::InvalidPosition)?;
+    let operations = parse_epd_operations(rest)?;
+
+    let mut epd = Epd {
+        board,
+        operations,
+        best_moves: vec![],
+        avoid_moves: vec![],
+    };
+    if let Some(halfmove_clock) = epd.get_halfmove_clock() {
+        epd.board = epd.board.set_halfmove_clock(halfmove_clock);
+    }
+    if let Some(fullmove_number) = epd.get_fullmove_number().filter(|number| *number > 0) {
+        epd.board = epd.board.set_fullmove_number(fullmove_number);
+    }
+    let parse_moves = |opcode| -> Result<Vec<Move>, EpdError> {
+        epd.get_operands(opcode)
+            .unwrap_or_default()
+            .iter()
+            .map(|m| {
+                parse_annotated_san_move(&epd.board, m)
+                    .map(normalize_promotion)
+                    .map_err(EpdError::InvalidMove)
+            })
+            .collect()
+    };
+    let best_moves = parse_moves("bm")?;
+    let avoid_moves = parse_moves("am")?;
+    epd.best_moves = best_moves;
+    epd.avoid_moves = avoid_moves;
+    Ok(epd)
+}
+
+// a game from a PGN (Portable Game Notation) file, with its tags, the
+// position it started from, and its moves
+#[derive(Clone, Debug, PartialEq)]
+pub struct PgnGame {
+    tags: Vec<(String, String)>,
+    start: Board,
+    moves: Vec<Move>,
+    result: String,
+}
+
+impl PgnGame {
+    // the value of a tag, such as "Magnus Carlsen" for `[White "Magnus Carlsen"]`
+    pub fn get_tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn get_tags(&self) -> &[(String, String)] {
+        &self.tags
+    }
+
+    // the position the game started from, which is the starting position
+    // unless the game has a `FEN` tag
+    pub fn get_start_board(&self) -> &Board {
+        &self.start
+    }
+
+    // the moves of the main line, leaving out any variations
+    pub fn get_moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    // the result of the game: "1-0", "0-1", "1/2-1/2", or "*" if it is unknown
+    pub fn get_result(&self) -> &str {
+        &self.result
+    }
+
+    // the position after every move of the game, starting with the position
+    // after the first move
+    pub fn get_boards(&self) -> impl Iterator<Item = Board> + '_ {
+        self.moves.iter().scan(self.start, |board, m| {
+            *board = board.apply_eval_move(*m);
+            Some(*board)
+        })
+    }
+}
+
+const PGN_RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+// parse a `[Name "value"]` tag pair
+fn parse_pgn_tag(line: &str) -> Result<(String, String), PgnError> {
+    let tag = line
+        .trim()
+        .strip_prefix('[')
+        .and_then(|tag| tag.strip_suffix(']'))
+        .ok_or(PgnError::InvalidTag)?;
+    let (name, value) = tag
+        .trim()
+        .split_once(char::is_whitespace)
+        .ok_or(PgnError::InvalidTag)?;
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .ok_or(PgnError::InvalidTag)?;
+    Ok((
+        name.to_string(),
+        value.replace("\\\"", "\"").replace("\\\\", "\\"),
+    ))
+}
+
+// split PGN movetext into its moves and result, leaving out move numbers,
+// comments, variations and annotation glyphs
+fn parse_pgn_movetext(movetext: &str) -> Result<(Vec<&str>, Option<&str>), PgnError> {
+    let mut tokens = vec![];
+    let mut result = None;
+    let mut depth = 0;
+    let mut rest = movetext;
+    while let Some(c) = rest.chars().next() {
+        match c {
+            '{' => {
+                let end = rest.find('}').ok_or(PgnError::UnclosedComment)?;
+                rest = &rest[end + 1..];
             }
+            ';' => rest = rest.split_once('\n').map_or("", |(_, rest)| rest),
+            '(' => {
+                depth += 1;
+                rest = &rest[1..];
+            }
+            ')' => {
+                if depth == 0 {
+                    return Err(PgnError::UnclosedVariation);
+                }
+                depth -= 1;
+                rest = &rest[1..];
+            }
+            c if c.is_whitespace() => rest = &rest[c.len_utf8()..],
+            _ => {
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || "{}();".contains(c))
+                    .unwrap_or(rest.len());
+                let token = &rest[..end];
+                rest = &rest[end..];
+                if depth > 0 || token.starts_with('$') {
+                    continue;
+                }
+                if PGN_RESULTS.contains(&token) {
+                    result = Some(token);
+                    continue;
+                }
+                // move numbers may be written apart from the move, as in
+                // "1. e4", or joined to it, as in "1.e4" or "1...e5"
+                let token = match token.split_once('.') {
+                    Some((number, token)) if number.chars().all(|c| c.is_ascii_digit()) => {
+                        token.trim_start_matches('.')
+                    }
+                    _ => token,
+                };
+                if !token.is_empty() {
+                    tokens.push(token);
+                }
+            }
+        }
+    }
+    if depth > 0 {
+        return Err(PgnError::UnclosedVariation);
+    }
+    Ok((tokens, result))
+}
+
+fn parse_pgn_game(tag_lines: &[&str], movetext: &str) -> Result<PgnGame, PgnError> {
+    let tags = tag_lines
+        .iter()
+        .map(|line| parse_pgn_tag(line))
+        .collect::<Result<Vec<_>, _>>()?;
+    let tag = |name| {
+        tags.iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    let mut start = match tag("FEN") {
+        Some(fen) => parse_fen(fen).map_err(PgnError::InvalidPosition)?,
+        None => Board::default(),
+    };
+    if let Some(variant) = tag("Variant").and_then(|name| name.parse::<Variant>().ok()) {
+        start = start.set_variant(variant);
+    }
+
+    let (tokens, result) = parse_pgn_movetext(movetext)?;
+    let mut board = start;
+    let mut moves = vec![];
+    for (ply, token) in tokens.into_iter().enumerate() {
+        let m =
+            parse_annotated_san_move(&board, token).map_err(|e| PgnError::InvalidMove(ply, e))?;
+        board = board.apply_eval_move(m);
+        moves.push(m);
+    }
+
+    let result = result.or(tag("Result")).unwrap_or("*").to_string();
+    Ok(PgnGame {
+        tags,
+        start,
+        moves,
+        result,
+    })
+}
+
+// parse every game of a PGN file
+//
+// a game that cannot be parsed does not stop the others from being read,
+// so a large collection with a few broken games can still be used
+pub fn parse_pgn(pgn: &str) -> Vec<Result<PgnGame, PgnError>> {
+    let mut games = vec![];
+    let mut tag_lines: Vec<&str> = vec![];
+    let mut movetext = String::new();
+    for line in pgn.lines() {
+        let trimmed = line.trim();
+        // lines starting with `%` are escaped, and ignored
+        if trimmed.starts_with('%') {
+            continue;
+        }
+        if trimmed.starts_with('[') && !movetext.trim().is_empty() {
+            games.push(parse_pgn_game(&tag_lines, &movetext));
+            tag_lines.clear();
+            movetext.clear();
         }
-        _ => Err("ambiguous move".to_string()),
+        if trimmed.starts_with('[') && movetext.trim().is_empty() {
+            tag_lines.push(trimmed);
+        } else {
+            movetext.push_str(line);
+            movetext.push('\n');
+        }
+    }
+    if !tag_lines.is_empty() || !movetext.trim().is_empty() {
+        games.push(parse_pgn_game(&tag_lines, &movetext));
     }
+    games
 }
 
 #[cfg(test)]
@@ -369,24 +994,24 @@ mod tests {
     use super::*;
     use crate::board::*;
     use crate::position::*;
-    use crate::{GameResult, Move};
+    use crate::{GameResult, Move, MoveParseError, PgnError, WHITE};
 
     #[test]
     fn test_fen() {
         let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 
         let board = Board::default();
-        assert_eq!(format_fen(&board, 0, 1).unwrap(), String::from(start));
+        assert_eq!(format_fen(&board).unwrap(), String::from(start));
 
         let board = parse_fen(start).unwrap();
-        assert_eq!(format_fen(&board, 0, 1).unwrap(), String::from(start));
+        assert_eq!(format_fen(&board).unwrap(), String::from(start));
 
         let board = match board.play_move(Move::Piece(E2, E4)) {
             GameResult::Continuing(board) => board,
             _ => panic!("e4 failed"),
         };
         assert_eq!(
-            format_fen(&board, 0, 1).unwrap(),
+            format_fen(&board).unwrap(),
             "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1".to_string()
         );
 
@@ -395,7 +1020,7 @@ mod tests {
             _ => panic!("c5 failed"),
         };
         assert_eq!(
-            format_fen(&board, 0, 2).unwrap(),
+            format_fen(&board).unwrap(),
             "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2".to_string()
         );
 
@@ -404,11 +1029,103 @@ mod tests {
             _ => panic!("Nf3 failed"),
         };
         assert_eq!(
-            format_fen(&board, 1, 2).unwrap(),
+            format_fen(&board).unwrap(),
             "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2".to_string()
         )
     }
 
+    #[test]
+    fn test_fen_round_trip() {
+        let corpus = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4",
+            "8/8/4k3/8/8/4K3/8/8 b - - 99 300",
+            "4k3/8/8/8/8/8/8/4K2R w K - 65535 65535",
+            "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1",
+            "4k3/8/8/8/8/8/8/4K3[QRBNPqrbnp] b - - 12 34",
+        ];
+        for fen in corpus {
+            assert_eq!(format_fen(&parse_fen(fen).unwrap()).unwrap(), fen);
+        }
+
+        // every position reached from the corpus also round trips
+        for fen in corpus {
+            let board = parse_fen(fen).unwrap();
+            for m in board.get_legal_moves() {
+                if let GameResult::Continuing(next) = board.play_move(m) {
+                    let next_fen = format_fen(&next).unwrap();
+                    assert_eq!(parse_fen(&next_fen).unwrap(), next, "{}", next_fen);
+                    assert_eq!(
+                        format_fen(&parse_fen(&next_fen).unwrap()).unwrap(),
+                        next_fen
+                    );
+                }
+            }
+        }
+
+        // the counters can be left out together, as in EPD
+        let board = parse_fen("4k3/8/8/8/8/8/8/4K3 w - -").unwrap();
+        assert_eq!(board.get_halfmove_clock(), 0);
+        assert_eq!(board.get_fullmove_number(), 1);
+        assert_eq!(format_fen(&board).unwrap(), "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+
+        let invalid = |fen| parse_fen(fen).unwrap_err();
+        assert_eq!(
+            invalid("4k3/8/8/8/8/8/8/4K3 w - - 0"),
+            FenError::WrongFieldCount
+        );
+        assert_eq!(
+            invalid("4k3/8/8/8/8/8/8/4K3 w - - 0 1 0"),
+            FenError::WrongFieldCount
+        );
+        assert_eq!(
+            invalid("4k3/8/8/8/8/8/8/4K3 w - - -1 1"),
+            FenError::InvalidHalfmoveClock
+        );
+        assert_eq!(
+            invalid("4k3/8/8/8/8/8/8/4K3 w - - 0 65536"),
+            FenError::InvalidFullmoveNumber
+        );
+        assert_eq!(
+            invalid("4k3/8/8/8/8/8/8/4K3 w - - 0 0"),
+            FenError::InvalidFullmoveNumber
+        );
+
+        // the placement must have exactly eight ranks of eight squares
+        assert_eq!(
+            invalid("4k3/8/8/8/8/8/8/4K2 w - - 0 1"),
+            FenError::RankTooShort
+        );
+        assert_eq!(
+            invalid("4k3/8/8/8/8/8/8/4K4 w - - 0 1"),
+            FenError::RankTooLong
+        );
+        assert_eq!(
+            invalid("4k3/8/8/8/8/8/9/4K3 w - - 0 1"),
+            FenError::RankTooLong
+        );
+        assert_eq!(
+            invalid("4k3/8/8/8/8/8/8/4K\u{b2}3 w - - 0 1"),
+            FenError::InvalidPiece('\u{b2}')
+        );
+        assert_eq!(
+            invalid("4k3/8/8/8/8/8/8/4K*3 w - - 0 1"),
+            FenError::InvalidPiece('*')
+        );
+        assert_eq!(invalid("4k3/8/8/8/8/8/8 w - - 0 1"), FenError::MissingRanks);
+        assert_eq!(
+            parse_fen_lenient("4k3/8/8 w - - 0 1").unwrap_err(),
+            FenError::MissingRanks
+        );
+        assert_eq!(
+            parse_fen_lenient("8/8/8/8/8/8/8/8/[] w - - 0 1").unwrap_err(),
+            FenError::TooManyRanks
+        );
+    }
+
     #[test]
     fn test_parse_san_move() {
         let mut board = Board::default();
@@ -425,7 +1142,7 @@ mod tests {
         // not valid first move for white
         assert_eq!(
             parse_san_move(&board, "d5").expect_err("d5"),
-            "no matching move".to_string()
+            SanError::NoMatchingMove
         );
 
         // make first move
@@ -441,7 +1158,218 @@ mod tests {
         // white moves not valid for black
         assert_eq!(
             parse_san_move(&board, "c4").expect_err("c4"),
-            "no matching move".to_string()
+            SanError::NoMatchingMove
+        );
+    }
+
+    #[test]
+    fn test_uci_move() {
+        let board = parse_fen("r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1").unwrap();
+        assert_eq!(parse_uci_move(&board, "e5d6"), Ok(Move::Piece(E5, D6)));
+        assert_eq!(parse_uci_move(&board, "e1g1"), Ok(Move::KingSideCastle));
+        assert_eq!(parse_uci_move(&board, "e1c1"), Ok(Move::QueenSideCastle));
+        assert_eq!(parse_uci_move(&board, "e1f1"), Ok(Move::Piece(E1, F1)));
+        assert_eq!(
+            parse_uci_move(&board, "b7a8n"),
+            Ok(Move::Promotion(B7, A8, Piece::Knight(WHITE, A8)))
+        );
+        assert_eq!(parse_uci_move(&board, "0000"), Ok(Move::Null));
+        assert_eq!(
+            parse_uci_move(&board, "b7b8p"),
+            Err(MoveParseError::InvalidPromotion)
+        );
+        assert_eq!(
+            parse_uci_move(&board, "b7b8Q"),
+            Err(MoveParseError::InvalidPromotion)
+        );
+        assert_eq!(
+            parse_uci_move(&board, "e2e4e"),
+            Err(MoveParseError::InvalidPromotion)
+        );
+        assert_eq!(
+            parse_uci_move(&board, "e2"),
+            Err(MoveParseError::InvalidFormat)
+        );
+        assert!(matches!(
+            parse_uci_move(&board, "e9e4"),
+            Err(MoveParseError::InvalidSquare(_))
+        ));
+        assert_eq!(format_uci_move(&board, Move::QueenSideCastle), "e1c1");
+        assert_eq!(
+            format_uci_move(&board, Move::Promotion(B7, B8, Piece::Queen(WHITE, B8))),
+            "b7b8q"
+        );
+
+        // every legal move round trips, including castling in Chess960
+        // and drops in crazyhouse
+        for fen in [
+            "r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1",
+            "r3k2r/8/8/8/8/8/1p6/R3K2R b KQkq - 0 1",
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRK1 w Ff - 2 9",
+            "4k3/8/8/8/8/8/8/R3K1R1 w AG - 0 1",
+            "4k3/8/8/8/8/8/8/4K3[QNp] w - - 0 1",
+        ] {
+            let board = parse_fen(fen).unwrap();
+            for m in board.get_legal_moves() {
+                let uci = format_uci_move(&board, m);
+                assert_eq!(parse_uci_move(&board, &uci), Ok(m), "{} in {}", uci, fen);
+            }
+        }
+        let board = parse_fen("4k3/8/8/8/8/8/8/4K3[N] w - - 0 1").unwrap();
+        assert_eq!(
+            format_uci_move(&board, Move::Drop(PieceKind::Knight, F3)),
+            "N@f3"
+        );
+        assert_eq!(
+            parse_uci_move(&board, "N@f3"),
+            Ok(Move::Drop(PieceKind::Knight, F3))
+        );
+    }
+
+    #[test]
+    fn test_parse_pgn() {
+        let pgn = r#"[Event "Casual game"]
+[White "Anderssen, Adolf"]
+[Black "Kieseritzky, Lionel"]
+[Result "1-0"]
+
+1. e4 e5 2. f4 exf4 3. Bc4 Qh4+ {the queen checks} 4. Kf1 b5?! (4... Nf6 5. Nf3)
+5. Bxb5 Nf6 $6 6.Nf3 Qh6 7. d3 Nh5 ; the knight attacks g3
+8. Nh4 1-0
+
+[Event "Castling"]
+[FEN "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1"]
+
+1. O-O 0-0-0 *
+
+[Event "Broken"]
+
+1. e4 e5 2. Ke3 1/2-1/2
+
+[Event "Unclosed"]
+
+1. d4 {a comment 1-0
+"#;
+        let games = parse_pgn(pgn);
+        assert_eq!(games.len(), 4);
+
+        let game = games[0].as_ref().unwrap();
+        assert_eq!(game.get_tag("White"), Some("Anderssen, Adolf"));
+        assert_eq!(game.get_tag("Round"), None);
+        assert_eq!(game.get_result(), "1-0");
+        assert_eq!(game.get_moves().len(), 15);
+        assert_eq!(game.get_moves()[0], Move::Piece(E2, E4));
+        assert_eq!(game.get_moves()[8], Move::Piece(C4, B5));
+        assert_eq!(
+            format_fen(&game.get_boards().last().unwrap()).unwrap(),
+            "rnb1kb1r/p1pp1ppp/7q/1B5n/4Pp1N/3P4/PPP3PP/RNBQ1K1R b kq - 2 8"
+        );
+
+        let game = games[1].as_ref().unwrap();
+        assert_eq!(
+            game.get_moves(),
+            &[Move::KingSideCastle, Move::QueenSideCastle]
+        );
+        assert_eq!(game.get_result(), "*");
+
+        assert!(matches!(games[2], Err(PgnError::InvalidMove(2, _))));
+        assert_eq!(games[3], Err(PgnError::UnclosedComment));
+        assert_eq!(
+            parse_pgn("[Event Casual]\n\n1. e4 *")[0],
+            Err(PgnError::InvalidTag)
+        );
+        assert_eq!(
+            parse_pgn("1. e4 (1. d4 *")[0],
+            Err(PgnError::UnclosedVariation)
+        );
+    }
+
+    #[test]
+    fn test_chess960_fen() {
+        let board = Board::chess960(0).unwrap();
+        let xfen = "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1";
+        let shredder = "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf - 0 1";
+        assert_eq!(format_fen(&board).unwrap(), xfen);
+        assert_eq!(format_shredder_fen(&board).unwrap(), shredder);
+        assert_eq!(parse_fen(xfen).unwrap(), board);
+        assert_eq!(parse_fen(shredder).unwrap(), board);
+
+        // an inner rook is named by its file in X-FEN
+        let fen = "rk2r2r/8/8/8/8/8/8/RK2R2R w Ee - 0 1";
+        let board = parse_fen(fen).unwrap();
+        assert!(board.is_chess960());
+        assert_eq!(
+            board
+                .get_castling_rights(Color::White)
+                .get_kingside_rook_col(),
+            Some(4)
+        );
+        assert_eq!(format_fen(&board).unwrap(), fen);
+
+        let board = Board::default();
+        assert_eq!(
+            format_shredder_fen(&board).unwrap(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w HAha - 0 1"
+        );
+    }
+    #[test]
+    fn test_parse_epd() {
+        let epd = parse_epd(
+            "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\"; \
+             c0 \"mate in three; Qg6 fxg6\"; acd 12; ce 32000",
+        )
+        .unwrap();
+        assert_eq!(epd.get_id(), Some("WAC.001"));
+        assert_eq!(epd.get_comment(), Some("mate in three; Qg6 fxg6"));
+        assert_eq!(epd.get_analysis_depth(), Some(12));
+        assert_eq!(epd.get_centipawn_eval(), Some(32000));
+        assert_eq!(epd.get_best_moves(), &[Move::Piece(G3, G6)]);
+        assert!(epd.get_avoid_moves().is_empty());
+        assert!(epd.is_solved_by(Move::Piece(G3, G6)));
+        assert!(!epd.is_solved_by(Move::Piece(G3, H3)));
+        assert_eq!(epd.get_operands("pv"), None);
+        assert_eq!(epd.get_board().get_turn_color(), WHITE);
+        assert_eq!(epd.get_board().get_fullmove_number(), 1);
+
+        let epd = parse_epd("4k3/8/8/8/8/8/8/4K3 b - - hmvc 7; fmvn 40;").unwrap();
+        assert_eq!(
+            format_fen(epd.get_board()).unwrap(),
+            "4k3/8/8/8/8/8/8/4K3 b - - 7 40"
+        );
+
+        let epd = parse_epd("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - am O-O+ Ra8+; bm O-O-O e1d1;");
+        assert!(matches!(epd, Err(EpdError::InvalidMove(_))));
+        let epd =
+            parse_epd("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - am O-O Rxa8+; bm O-O-O Kd1;").unwrap();
+        assert_eq!(
+            epd.get_avoid_moves(),
+            &[Move::KingSideCastle, Move::Piece(A1, A8)]
+        );
+        assert!(epd.is_solved_by(Move::QueenSideCastle));
+        assert!(!epd.is_solved_by(Move::KingSideCastle));
+        assert!(!epd.is_solved_by(Move::Piece(H1, H8)));
+
+        // promotions match the engine's moves by their squares and piece
+        let epd = parse_epd("8/1P3k2/8/8/8/8/8/4K3 w - - bm b8=Q+; am b8=R;").unwrap();
+        assert_eq!(epd.get_best_moves(), &[Move::Piece(B7, B8)]);
+        assert_eq!(
+            epd.get_avoid_moves(),
+            &[Move::Promotion(B7, B8, Piece::Rook(WHITE, B8))]
+        );
+        assert!(epd.is_solved_by(Move::Piece(B7, B8)));
+        assert!(epd.is_solved_by(Move::Promotion(B7, B8, Piece::Queen(WHITE, B8))));
+        assert!(!epd.is_solved_by(Move::Promotion(B7, B8, Piece::Knight(WHITE, B8))));
+        let epd = parse_epd("8/1P3k2/8/8/8/8/8/4K3 w - - bm b8=N;").unwrap();
+        assert!(epd.is_solved_by(Move::Promotion(B7, B8, Piece::Knight(WHITE, B8))));
+        assert!(!epd.is_solved_by(Move::Piece(B7, B8)));
+
+        assert!(matches!(
+            parse_epd("8/8/8/8/8/8/8/8 w - - id \"empty\";"),
+            Err(EpdError::InvalidPosition(_))
+        ));
+        assert_eq!(
+            parse_epd("4k3/8/8/8/8/8/8/4K3 w - - id \"open;"),
+            Err(EpdError::UnclosedString)
         );
     }
 }
@@ -0,0 +1,635 @@
+use crate::board::{Board, BoardBuilder};
+use crate::eval::EvalParams;
+use crate::piece::{Piece, PieceKind};
+use crate::position::*;
+use crate::{Color, DrawReason, GameResult, Move, BLACK, WHITE};
+use alloc::string::String;
+
+/// A set of rules the game is played by.
+///
+/// A variant can change the starting position, add ways to win or draw
+/// the game, and forbid moves that would be legal in regular chess.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Variant {
+    /// Regular chess.
+    #[default]
+    Standard,
+    /// White has 36 pawns and no king. Black wins by capturing every white
+    /// piece, and White wins by checkmating the black king. White pawns on
+    /// the first rank may move two squares.
+    Horde,
+    /// A player also wins by moving their king onto one of the four
+    /// center squares.
+    KingOfTheHill,
+    /// A player also wins by giving check for the third time.
+    ThreeCheck,
+    /// Both kings race to the eighth rank, and neither player may give
+    /// check. If White gets there first, Black has one more move to draw
+    /// by reaching it as well.
+    RacingKings,
+    /// Every capture explodes, removing the capturing piece and every piece
+    /// other than a pawn around the captured square. A player wins by
+    /// exploding the enemy king, or by checkmate. Kings cannot capture, and
+    /// kings standing next to each other cannot be in check.
+    Atomic,
+    /// Captures are compulsory, and the king is a regular piece that cannot
+    /// castle, can be captured, and can be promoted to. A player wins by
+    /// losing all of their pieces, or by having no legal moves.
+    Antichess,
+    /// Captured pieces join the captor's pocket, and can later be dropped
+    /// onto any empty square instead of making a move. Promoted pieces
+    /// return to the pocket as pawns when captured.
+    Crazyhouse,
+}
+
+// the number of pieces a player has left
+fn count_pieces(board: &Board, color: Color) -> usize {
+    (0..8)
+        .flat_map(|row| (0..8).map(move |col| Position::new(row, col)))
+        .filter(|pos| board.has_ally_piece(*pos, color))
+        .count()
+}
+
+impl Variant {
+    pub const ALL: [Self; 8] = [
+        Self::Standard,
+        Self::Horde,
+        Self::KingOfTheHill,
+        Self::ThreeCheck,
+        Self::RacingKings,
+        Self::Atomic,
+        Self::Antichess,
+        Self::Crazyhouse,
+    ];
+
+    /// Get the name of this variant, as used by `FromStr`.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Horde => "horde",
+            Self::KingOfTheHill => "kingofthehill",
+            Self::ThreeCheck => "threecheck",
+            Self::RacingKings => "racingkings",
+            Self::Atomic => "atomic",
+            Self::Antichess => "antichess",
+            Self::Crazyhouse => "crazyhouse",
+        }
+    }
+
+    /// Get the starting position for this variant.
+    pub fn get_start_position(&self) -> Board {
+        match self {
+            Self::Horde => Board::horde(),
+            Self::RacingKings => BoardBuilder::default()
+                .piece(Piece::King(BLACK, A2))
+                .piece(Piece::Rook(BLACK, B2))
+                .piece(Piece::Bishop(BLACK, C2))
+                .piece(Piece::Knight(BLACK, D2))
+                .piece(Piece::Knight(WHITE, E2))
+                .piece(Piece::Bishop(WHITE, F2))
+                .piece(Piece::Rook(WHITE, G2))
+                .piece(Piece::King(WHITE, H2))
+                .piece(Piece::Queen(BLACK, A1))
+                .piece(Piece::Rook(BLACK, B1))
+                .piece(Piece::Bishop(BLACK, C1))
+                .piece(Piece::Knight(BLACK, D1))
+                .piece(Piece::Knight(WHITE, E1))
+                .piece(Piece::Bishop(WHITE, F1))
+                .piece(Piece::Rook(WHITE, G1))
+                .piece(Piece::Queen(WHITE, H1))
+                .build()
+                .set_variant(*self),
+            Self::Antichess => BoardBuilder::from(Board::default())
+                .disable_castling()
+                .build()
+                .set_variant(*self),
+            _ => Board::default().set_variant(*self),
+        }
+    }
+
+    /// Can the game end in a dead position, where neither player can
+    /// win by checkmate? Only regular chess is decided by checkmate alone.
+    pub(crate) fn has_dead_positions(&self) -> bool {
+        *self == Self::Standard
+    }
+
+    /// Must a player capture whenever they can?
+    pub(crate) fn has_forced_captures(&self) -> bool {
+        *self == Self::Antichess
+    }
+
+    /// Can a player's king be in check? This is only asked when the
+    /// player has a king.
+    pub(crate) fn can_be_in_check(&self, board: &Board, color: Color) -> bool {
+        match self {
+            Self::Antichess => false,
+            // capturing a king next to its own king would explode both,
+            // and once the enemy king is gone the game is over
+            Self::Atomic => match (board.get_king_pos(color), board.get_king_pos(!color)) {
+                (Some(king_pos), Some(enemy_king_pos)) => !king_pos.is_adjacent_to(enemy_king_pos),
+                _ => false,
+            },
+            _ => true,
+        }
+    }
+
+    /// Does this variant allow a move that is legal in regular chess?
+    pub(crate) fn is_legal_move(&self, board: &Board, m: Move, color: Color) -> bool {
+        match self {
+            // giving check is not allowed
+            Self::RacingKings => !board.apply_move(m).is_in_check(!color),
+            // kings cannot capture, and a player cannot explode their own king
+            Self::Atomic => {
+                let king_captures = match m {
+                    Move::Piece(from, _) => {
+                        board.get_piece(from).is_some_and(|piece| piece.is_king())
+                            && board.is_capture(m)
+                    }
+                    _ => false,
+                };
+                !king_captures
+                    && (board.get_king_pos(color).is_none()
+                        || board.apply_move(m).get_king_pos(color).is_some())
+            }
+            Self::Antichess => !matches!(m, Move::KingSideCastle | Move::QueenSideCastle),
+            _ => true,
+        }
+    }
+
+    /// Get the squares that a capture on the given square explodes in
+    /// atomic chess, besides the captured square itself.
+    pub(crate) fn get_explosion(center: Position) -> impl Iterator<Item = Position> {
+        (-1..=1)
+            .flat_map(move |row| (-1..=1).map(move |col| (row, col)))
+            .filter(|offset| *offset != (0, 0))
+            .map(move |(row, col)| Position::new(center.get_row() + row, center.get_col() + col))
+            .filter(|pos| pos.is_on_board())
+    }
+
+    /// Has the game ended by a rule of this variant, with the given
+    /// board's current player to move?
+    pub(crate) fn get_outcome(&self, board: &Board) -> Option<GameResult> {
+        match self {
+            Self::Standard | Self::Crazyhouse => None,
+            Self::Horde => {
+                if count_pieces(board, WHITE) == 0 {
+                    Some(GameResult::Victory(BLACK))
+                } else {
+                    None
+                }
+            }
+            Self::KingOfTheHill => [WHITE, BLACK].into_iter().find_map(|color| {
+                let king_pos = board.get_king_pos(color)?;
+                if [D4, E4, D5, E5].contains(&king_pos) {
+                    Some(GameResult::Victory(color))
+                } else {
+                    None
+                }
+            }),
+            Self::ThreeCheck => [WHITE, BLACK].into_iter().find_map(|color| {
+                if board.get_checks_given(color) >= 3 {
+                    Some(GameResult::Victory(color))
+                } else {
+                    None
+                }
+            }),
+            Self::Atomic => [WHITE, BLACK].into_iter().find_map(|color| {
+                if board.get_king_pos(color).is_none() {
+                    Some(GameResult::Victory(!color))
+                } else {
+                    None
+                }
+            }),
+            Self::Antichess => {
+                if board.generate_moves().is_empty() {
+                    Some(GameResult::Victory(board.get_turn_color()))
+                } else {
+                    None
+                }
+            }
+            Self::RacingKings => {
+                let reached_goal = |color| {
+                    board
+                        .get_king_pos(color)
+                        .is_some_and(|pos| pos.get_row() == 7)
+                };
+                match (reached_goal(WHITE), reached_goal(BLACK)) {
+                    (true, true) => Some(GameResult::Draw(DrawReason::VariantRule)),
+                    (false, true) => Some(GameResult::Victory(BLACK)),
+                    (true, false) => {
+                        // black gets one last move to catch up
+                        let catches_up = board.get_turn_color() == BLACK
+                            && board.get_king_pos(BLACK).is_some_and(|pos| {
+                                Piece::King(BLACK, pos)
+                                    .get_legal_moves(board)
+                                    .iter()
+                                    .any(|m| matches!(m, Move::Piece(_, to) if to.get_row() == 7))
+                            });
+                        if catches_up {
+                            None
+                        } else {
+                            Some(GameResult::Victory(WHITE))
+                        }
+                    }
+                    (false, false) => None,
+                }
+            }
+        }
+    }
+
+    /// Adjust a static evaluation for the goals of this variant, in the
+    /// same units as the material values.
+    pub(crate) fn adjust(
+        &self,
+        board: &Board,
+        color: Color,
+        value: f64,
+        params: &EvalParams,
+    ) -> f64 {
+        let for_color = |white_value: f64| match color {
+            Color::White => white_value,
+            Color::Black => -white_value,
+        };
+        match self {
+            Self::KingOfTheHill => {
+                let hill_distance = |color| {
+                    board.get_king_pos(color).map_or(0, |pos| {
+                        let col = (3 - pos.get_col()).max(pos.get_col() - 4).max(0);
+                        let row = (3 - pos.get_row()).max(pos.get_row() - 4).max(0);
+                        col.max(row)
+                    }) as f64
+                };
+                value + for_color(5.0 * (hill_distance(BLACK) - hill_distance(WHITE)))
+            }
+            Self::ThreeCheck => {
+                let checks =
+                    board.get_checks_given(WHITE) as f64 - board.get_checks_given(BLACK) as f64;
+                value + for_color(30.0 * checks)
+            }
+            Self::RacingKings => {
+                let rank = |color| board.get_king_pos(color).map_or(0, |pos| pos.get_row()) as f64;
+                value + for_color(10.0 * (rank(WHITE) - rank(BLACK)))
+            }
+            // material is a burden, and the king is just another piece
+            Self::Antichess => {
+                10.0 * (count_pieces(board, !color) as f64 - count_pieces(board, color) as f64)
+            }
+            // pieces in hand are worth as much as pieces on the board
+            Self::Crazyhouse => {
+                let pocket_value = |color| {
+                    PieceKind::ALL
+                        .into_iter()
+                        .map(|kind| {
+                            board.get_pocket_count(color, kind) as f64 * params.get_material(kind)
+                        })
+                        .sum::<f64>()
+                };
+                value + for_color(pocket_value(WHITE) - pocket_value(BLACK))
+            }
+            Self::Standard | Self::Horde | Self::Atomic => value,
+        }
+    }
+}
+
+impl core::fmt::Display for Variant {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> Result<(), core::fmt::Error> {
+        write!(f, "{}", self.get_name())
+    }
+}
+
+impl core::str::FromStr for Variant {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let name = name.to_ascii_lowercase().replace(['-', '_', ' '], "");
+        match name.as_str() {
+            "standard" | "chess" => Ok(Self::Standard),
+            "horde" => Ok(Self::Horde),
+            "kingofthehill" | "koth" => Ok(Self::KingOfTheHill),
+            "threecheck" | "3check" => Ok(Self::ThreeCheck),
+            "racingkings" => Ok(Self::RacingKings),
+            "atomic" => Ok(Self::Atomic),
+            "antichess" | "giveaway" | "suicide" => Ok(Self::Antichess),
+            "crazyhouse" | "zh" => Ok(Self::Crazyhouse),
+            _ => Err(format!("unknown variant `{}`", name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::perft;
+    use crate::util::{format_fen, parse_fen, parse_fen_lenient, parse_san_move};
+    use crate::Evaluate;
+    use crate::{FenError, IllegalMoveReason};
+    use alloc::string::ToString;
+
+    fn continuing(result: GameResult) -> Board {
+        match result {
+            GameResult::Continuing(board) => board,
+            other => panic!("expected the game to continue, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_names() {
+        for variant in Variant::ALL {
+            assert_eq!(variant.get_name().parse::<Variant>(), Ok(variant));
+        }
+        assert_eq!(
+            "King of the Hill".parse::<Variant>(),
+            Ok(Variant::KingOfTheHill)
+        );
+        assert!("bughouse".parse::<Variant>().is_err());
+    }
+
+    #[test]
+    fn test_horde() {
+        let board = Variant::Horde.get_start_position();
+        assert_eq!(board.get_variant(), Variant::Horde);
+        assert_eq!(count_pieces(&board, WHITE), 36);
+        assert!(!board.get_castling_rights(WHITE).can_kingside_castle());
+
+        // pawns on the first rank can move two squares
+        let board = parse_fen_lenient("4k3/8/8/8/8/8/8/P7 w - - 0 1")
+            .unwrap()
+            .set_variant(Variant::Horde);
+        continuing(board.play_move(Move::Piece(A1, A3)));
+
+        // capturing the last white piece wins for black
+        let board = parse_fen_lenient("4k3/8/8/8/8/8/3p4/4P3 b - - 0 1")
+            .unwrap()
+            .set_variant(Variant::Horde);
+        assert_eq!(
+            board.play_move(Move::Promotion(D2, E1, Piece::Queen(BLACK, E1))),
+            GameResult::Victory(BLACK)
+        );
+    }
+
+    #[test]
+    fn test_king_of_the_hill() {
+        let board = parse_fen("4k3/8/8/8/8/4K3/8/8 w - - 0 1")
+            .unwrap()
+            .set_variant(Variant::KingOfTheHill);
+        assert_eq!(
+            board.play_move(Move::Piece(E3, E4)),
+            GameResult::Victory(WHITE)
+        );
+        continuing(board.play_move(Move::Piece(E3, E2)));
+
+        // the search goes for the hill
+        let m = board.get_best_next_move(1).0.unwrap();
+        assert!(matches!(m, Move::Piece(E3, to) if [D4, E4].contains(&to)));
+    }
+
+    #[test]
+    fn test_three_check() {
+        let mut board = parse_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1")
+            .unwrap()
+            .set_variant(Variant::ThreeCheck);
+        board = continuing(board.play_move(Move::Piece(A1, A8)));
+        board = continuing(board.play_move(Move::Piece(E8, E7)));
+        assert_eq!(board.get_checks_given(WHITE), 1);
+        board = continuing(board.play_move(Move::Piece(A8, A7)));
+        board = continuing(board.play_move(Move::Piece(E7, E6)));
+        assert_eq!(board.get_checks_given(WHITE), 2);
+        board = continuing(board.play_move(Move::Piece(E1, E2)));
+        board = continuing(board.play_move(Move::Piece(E6, E5)));
+        assert_eq!(
+            board.play_move(Move::Piece(A7, A5)),
+            GameResult::Victory(WHITE)
+        );
+    }
+
+    #[test]
+    fn test_racing_kings() {
+        let board = Variant::RacingKings.get_start_position();
+        assert_eq!(board.get_legal_moves().len(), 21);
+
+        // giving check is illegal
+        let board = parse_fen_lenient("8/8/8/8/8/k7/8/R6K w - - 0 1")
+            .unwrap()
+            .set_variant(Variant::RacingKings);
+        assert!(matches!(
+            board.play_move(Move::Piece(A1, A2)),
+            GameResult::IllegalMove(_)
+        ));
+
+        // black can still catch up after white reaches the goal
+        let board = parse_fen("8/k5K1/8/8/8/8/8/8 w - - 0 1")
+            .unwrap()
+            .set_variant(Variant::RacingKings);
+        let board = continuing(board.play_move(Move::Piece(G7, G8)));
+        assert_eq!(
+            board.play_move(Move::Piece(A7, A8)),
+            GameResult::Draw(DrawReason::VariantRule)
+        );
+
+        // but not from further away
+        let board = parse_fen("8/6K1/k7/8/8/8/8/8 w - - 0 1")
+            .unwrap()
+            .set_variant(Variant::RacingKings);
+        assert_eq!(
+            board.play_move(Move::Piece(G7, G8)),
+            GameResult::Victory(WHITE)
+        );
+    }
+
+    fn variant_fen(fen: &str, variant: Variant) -> Board {
+        parse_fen_lenient(fen).unwrap().set_variant(variant)
+    }
+
+    #[test]
+    fn test_atomic_perft() {
+        let board = Variant::Atomic.get_start_position();
+        assert_eq!(perft(&board, 3), 8902);
+
+        let board = variant_fen(
+            "rn2kb1r/1pp1p2p/p2q1pp1/3P4/2P3b1/4PN2/PP3PPP/R2QKB1R b KQkq - 0 1",
+            Variant::Atomic,
+        );
+        assert_eq!(perft(&board, 1), 40);
+        assert_eq!(perft(&board, 2), 1238);
+
+        let board = variant_fen(
+            "rn1qkb1r/p5pp/2p5/3p4/N3P3/5P2/PPP4P/R1BQK3 w Qkq - 0 1",
+            Variant::Atomic,
+        );
+        assert_eq!(perft(&board, 1), 28);
+        assert_eq!(perft(&board, 2), 833);
+    }
+
+    #[test]
+    fn test_atomic() {
+        // capturing explodes the surrounding pieces, but not pawns
+        let board = variant_fen("4k3/8/8/2nrp3/8/8/3Q4/4K3 w - - 0 1", Variant::Atomic);
+        let board = continuing(board.play_move(Move::Piece(D2, D5)));
+        assert!(board.has_no_piece(D5) && board.has_no_piece(C5));
+        assert_eq!(board.get_piece(E5), Some(Piece::Pawn(BLACK, E5)));
+        assert!(board.has_no_piece(D2));
+
+        // exploding the enemy king wins, even out of check
+        let board = variant_fen("3qk3/4p3/8/8/8/8/4R3/3K4 w - - 0 1", Variant::Atomic);
+        assert_eq!(
+            board.play_move(Move::Piece(E2, E7)),
+            GameResult::Victory(WHITE)
+        );
+
+        // kings cannot capture, or explode themselves
+        let board = variant_fen("4k3/8/8/8/8/8/3p4/4K3 w - - 0 1", Variant::Atomic);
+        assert!(matches!(
+            board.play_move(Move::Piece(E1, D2)),
+            GameResult::IllegalMove(_)
+        ));
+        let board = variant_fen("4k3/8/8/8/8/8/3pR3/4K3 w - - 0 1", Variant::Atomic);
+        assert!(matches!(
+            board.play_move(Move::Piece(E2, D2)),
+            GameResult::IllegalMove(_)
+        ));
+
+        // connected kings are never in check
+        let board = variant_fen("8/8/8/8/8/3k4/3K4/7r w - - 0 1", Variant::Atomic);
+        assert!(!board.is_in_check(WHITE));
+    }
+
+    #[test]
+    fn test_antichess_perft() {
+        let board = Variant::Antichess.get_start_position();
+        assert_eq!(perft(&board, 3), 8067);
+
+        let board = variant_fen("8/1p6/8/8/8/8/P7/8 w - - 0 1", Variant::Antichess);
+        assert_eq!(perft(&board, 1), 2);
+        assert_eq!(perft(&board, 2), 4);
+        assert_eq!(perft(&board, 3), 4);
+        assert_eq!(perft(&board, 4), 3);
+        assert_eq!(perft(&board, 5), 1);
+    }
+
+    #[test]
+    fn test_antichess() {
+        // captures are forced, and the king can be captured
+        let board = variant_fen("8/8/8/3k4/8/8/8/3QK3 w - - 0 1", Variant::Antichess);
+        assert!(matches!(
+            board.play_move(Move::Piece(E1, E2)),
+            GameResult::IllegalMove(_)
+        ));
+        assert_eq!(
+            board.play_move(Move::Piece(D1, D5)),
+            GameResult::Victory(BLACK)
+        );
+        assert!(!board.is_legal_move(Move::Piece(E1, E2), WHITE));
+        assert!(board.is_legal_move(Move::Piece(D1, D5), WHITE));
+        assert_eq!(
+            board.check_move(Move::Piece(E1, E2)),
+            Err(IllegalMoveReason::CaptureRequired)
+        );
+
+        // the search gives away its last piece
+        let board = variant_fen("7k/8/8/8/8/2r5/8/R7 w - - 0 1", Variant::Antichess);
+        let m = board.get_best_next_move(1).0.unwrap();
+        assert!(
+            m == Move::Piece(A1, A3) || m == Move::Piece(A1, C1),
+            "{}",
+            m
+        );
+
+        // pawns can promote to kings
+        let board = variant_fen("8/P7/8/8/8/8/8/7k w - - 0 1", Variant::Antichess);
+        let board = continuing(board.play_move(Move::Promotion(A7, A8, Piece::King(WHITE, A8))));
+        assert_eq!(board.get_piece(A8), Some(Piece::King(WHITE, A8)));
+    }
+
+    #[test]
+    fn test_crazyhouse_perft() {
+        let board = Variant::Crazyhouse.get_start_position();
+        assert_eq!(perft(&board, 2), 400);
+
+        let board = parse_fen("2k5/8/8/8/8/8/8/4K3[QRBNPqrbnp] w - - 0 1").unwrap();
+        assert_eq!(board.get_variant(), Variant::Crazyhouse);
+        assert_eq!(perft(&board, 1), 301);
+        assert_eq!(perft(&board, 2), 75353);
+    }
+
+    #[test]
+    fn test_crazyhouse() {
+        // captured pieces go to the captor's pocket
+        let board = parse_fen("4k3/8/8/3n4/4P3/8/8/4K3[] w - - 0 1").unwrap();
+        let board = continuing(board.play_move(Move::Piece(E4, D5)));
+        assert_eq!(board.get_pocket_count(WHITE, PieceKind::Knight), 1);
+
+        // and can be dropped, but pawns not on the last ranks
+        let board = board.change_turn();
+        assert!(matches!(
+            board.play_move(Move::Drop(PieceKind::Knight, D5)),
+            GameResult::IllegalMove(_)
+        ));
+        let board = continuing(board.play_move(Move::Drop(PieceKind::Knight, F6)));
+        assert_eq!(board.get_piece(F6), Some(Piece::Knight(WHITE, F6)));
+        assert_eq!(board.get_pocket_count(WHITE, PieceKind::Knight), 0);
+        let board = parse_fen("4k3/8/8/8/8/8/8/4K3[P] w - - 0 1").unwrap();
+        assert!(matches!(
+            board.play_move(Move::Drop(PieceKind::Pawn, A8)),
+            GameResult::IllegalMove(_)
+        ));
+
+        // promoted pieces return as pawns
+        let board = parse_fen("3nk3/2P5/8/8/8/8/8/4K3[] w - - 0 1").unwrap();
+        let board = continuing(board.play_move(Move::Promotion(C7, D8, Piece::Queen(WHITE, D8))));
+        assert!(board.is_promoted(D8));
+        assert_eq!(board.get_pocket_count(WHITE, PieceKind::Knight), 1);
+        let board = continuing(board.play_move(Move::Piece(E8, D8)));
+        assert_eq!(board.get_pocket_count(BLACK, PieceKind::Pawn), 1);
+        assert_eq!(board.get_pocket_count(BLACK, PieceKind::Queen), 0);
+
+        // in check, a drop must block it
+        let board = parse_fen("4k3/8/8/8/8/8/8/r3K3[N] w - - 0 1").unwrap();
+        for m in board.get_legal_moves() {
+            if let Move::Drop(_, to) = m {
+                assert!([B1, C1, D1].contains(&to), "{}", m);
+            }
+        }
+        continuing(board.play_move(Move::Drop(PieceKind::Knight, C1)));
+    }
+
+    #[test]
+    fn test_crazyhouse_fen_and_san() {
+        let fen = "r1bqk2r/pppp1ppp/2n2n2/4p3/1bB1P3/2N2N2/PPPP1PPP/R1BQK2R[Pp] w KQkq - 0 1";
+        let board = parse_fen(fen).unwrap();
+        assert_eq!(format_fen(&board).unwrap(), fen);
+        assert_eq!(parse_fen(&fen.replace("[Pp]", "/Pp")).unwrap(), board);
+        assert_eq!(
+            parse_san_move(&board, "P@d5"),
+            Ok(Move::Drop(PieceKind::Pawn, D5))
+        );
+        assert_eq!(
+            parse_san_move(&board, "@d5"),
+            Ok(Move::Drop(PieceKind::Pawn, D5))
+        );
+        assert!(parse_san_move(&board, "N@d5").is_err());
+        assert_eq!(Move::Drop(PieceKind::Knight, F3).to_string(), "N@f3");
+
+        let fen = "3Q~k3/8/8/8/8/8/8/4K3[n] b - - 0 1";
+        let board = parse_fen(fen).unwrap();
+        assert!(board.is_promoted(D8));
+        assert_eq!(format_fen(&board).unwrap(), fen);
+
+        // pocket counts are bytes
+        let pocket = "Q".repeat(255);
+        let board = parse_fen(&format!("4k3/8/8/8/8/8/8/4K3[{}] w - - 0 1", pocket)).unwrap();
+        assert_eq!(board.get_pocket_count(Color::White, PieceKind::Queen), 255);
+        assert_eq!(
+            parse_fen(&format!("4k3/8/8/8/8/8/8/4K3[{}Q] w - - 0 1", pocket)),
+            Err(FenError::InvalidPocket)
+        );
+    }
+
+    #[test]
+    fn test_crazyhouse_search() {
+        // mate by dropping the queen next to the king
+        let board = parse_fen("k7/8/1K6/8/8/8/8/8[Q] w - - 0 1").unwrap();
+        let m = board.get_best_next_move(1).0.unwrap();
+        assert!(matches!(m, Move::Drop(PieceKind::Queen, _)), "{}", m);
+        assert_eq!(board.play_move(m), GameResult::Victory(WHITE));
+    }
+}